@@ -4,7 +4,7 @@
 //  Created:
 //    07 Oct 2022, 21:50:45
 //  Last edited:
-//    13 Oct 2022, 10:41:37
+//    16 Oct 2026, 19:06:09
 //  Auto updated?
 //    Yes
 // 
@@ -12,9 +12,184 @@
 //!   Defines the abstract syntax tree for the compiled proxy language.
 // 
 
+use std::fmt::{Display, Formatter, Result as FResult};
+use std::net::{IpAddr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::errors::ParseError;
+use crate::parser::pattern::parse_port_number;
 use crate::spec::{Node, TextRange};
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::parser::parse;
+    use crate::tests::{scan_tokens, TEST_DIR};
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        // Canonical files should be rendered as-is
+        let source: String = fs::read_to_string(format!("{}/compile.pconf", TEST_DIR)).unwrap();
+        let tokens = scan_tokens(&source);
+        assert_eq!(parse(&tokens).unwrap().to_string(), source);
+
+        // Render a config with everything in it, and check that it parses back to the same thing
+        let source: &str = concat!(
+            "[settings]\n",
+            "port:80,name:\"Hello \\\"world\\\"\\n\\ttab \\\\ \\u{1F600}\",\n",
+            "negative: -42, secure: true,\n",
+            "empty: [], ports: [ 80, 443, ],\n",
+            "hosts: [ { host: \"a\", port: 8080, }, { } ],\n",
+            "nested: { inner: { deep: [ [ 1 ], [] ], }, },\n",
+            "[rules]\n",
+            "test.nl -> example.com,\n",
+            "http://example.com/api/*:8000-8099 -> https://backend.nl:443,\n",
+            "*1.example.com/*/info -> !accept,\n",
+            "example.com/ -> !drop 404 \"Not \\\"found\\\"\",\n",
+            ":80 -> 127.0.0.1, http://* -> !drop 500,\n",
+        );
+        let tokens = scan_tokens(source);
+        let config: Config = parse(&tokens).unwrap();
+        let rendered: String = config.to_string();
+        let tokens = scan_tokens(&rendered);
+        let reparsed: Config = parse(&tokens).unwrap();
+        assert_eq!(reparsed, config);
+        assert_eq!(reparsed.to_string(), rendered);
+        assert!(matches!(&config.config[0].settings[1].value, SettingValue::String(s, _) if s == "Hello \"world\"\n\ttab \\ \u{1F600}"));
+        assert!(rendered.contains("\nnegative: -42,\n"));
+        assert!(rendered.contains("\nhosts: [ { host: \"a\", port: 8080, }, {} ],\n"));
+        assert!(rendered.contains("\n*:80 -> 127.0.0.1,\n"));
+    }
+
+    #[test]
+    fn test_path_matches() {
+        // Specific paths only match exactly
+        let path: Path = Path::Specific(vec![ "api".into() ], TextRange::None);
+        assert!(path.matches("/api"));
+        assert!(path.matches("/api/"));
+        assert!(path.matches("/api?id=1"));
+        assert!(!path.matches("/api/users"));
+        assert!(!path.matches("/"));
+        assert!(!path.matches("/apiary"));
+
+        // Prefixes match anything under them
+        let path: Path = Path::Prefix(vec![ "api".into() ], TextRange::None);
+        assert!(path.matches("/api"));
+        assert!(path.matches("/api/users"));
+        assert!(path.matches("/api/users/1"));
+        assert!(!path.matches("/"));
+        assert!(!path.matches("/apiary/users"));
+
+        // The root only matches itself, unless it's a prefix
+        let path: Path = Path::Specific(vec![], TextRange::None);
+        assert!(path.matches("/"));
+        assert!(!path.matches("/api"));
+        let path: Path = Path::Prefix(vec![], TextRange::None);
+        assert!(path.matches("/"));
+        assert!(path.matches("/api"));
+
+        // Aterisk segments match any single segment
+        let path: Path = Path::Specific(vec![ "api".into(), "*1".into(), "info".into() ], TextRange::None);
+        assert!(path.matches("/api/users/info"));
+        assert!(!path.matches("/api/users"));
+        assert!(Path::Wildcard.matches("/anything/at/all"));
+    }
+
+    #[test]
+    fn test_from_str() {
+        // Protocols, with or without their separator
+        assert_eq!(Protocol::from_str("http").unwrap(), Protocol::Specific("http".into(), TextRange::None));
+        assert_eq!(Protocol::from_str("https://").unwrap(), Protocol::Specific("https".into(), TextRange::None));
+        assert_eq!(Protocol::from_str("*").unwrap(), Protocol::Wildcard);
+        assert!(matches!(Protocol::from_str(""), Err(ParseError::IllegalPatternError{ part: "protocol", .. })));
+        assert!(matches!(Protocol::from_str("ht tp"), Err(ParseError::IllegalPatternError{ part: "protocol", .. })));
+
+        // Endpoints
+        assert_eq!(Endpoint::from_str("example.com").unwrap(), Endpoint::Specific("example.com".into(), TextRange::None));
+        assert_eq!(Endpoint::from_str("*.my-site.nl").unwrap(), Endpoint::Specific("*.my-site.nl".into(), TextRange::None));
+        assert_eq!(Endpoint::from_str("::1").unwrap(), Endpoint::Specific("::1".into(), TextRange::None));
+        assert_eq!(Endpoint::from_str("[::1]").unwrap(), Endpoint::Specific("[::1]".into(), TextRange::None));
+        assert!(matches!(Endpoint::from_str("[a.nl]"), Err(ParseError::IllegalPatternError{ part: "endpoint", .. })));
+        assert_eq!(Endpoint::from_str("*").unwrap(), Endpoint::Wildcard);
+        assert!(matches!(Endpoint::from_str("a..b"), Err(ParseError::IllegalPatternError{ part: "endpoint", .. })));
+        assert!(matches!(Endpoint::from_str("a/b"), Err(ParseError::IllegalPatternError{ part: "endpoint", .. })));
+
+        // Paths, including the way they are displayed
+        assert_eq!(Path::from_str("/").unwrap(), Path::Specific(vec![], TextRange::None));
+        assert_eq!(Path::from_str("/api/users").unwrap(), Path::Specific(vec![ "api".into(), "users".into() ], TextRange::None));
+        assert_eq!(Path::from_str("/api/*1/info").unwrap(), Path::Specific(vec![ "api".into(), "*1".into(), "info".into() ], TextRange::None));
+        assert_eq!(Path::from_str("/api/*").unwrap(), Path::Prefix(vec![ "api".into() ], TextRange::None));
+        assert_eq!(Path::from_str("/ *").unwrap(), Path::Prefix(vec![], TextRange::None));
+        assert_eq!(Path::from_str("*").unwrap(), Path::Wildcard);
+        let path: Path = Path::from_str("/api/*1/*").unwrap();
+        assert_eq!(Path::from_str(&path.to_string()).unwrap(), path);
+        assert!(matches!(Path::from_str("api"), Err(ParseError::IllegalPatternError{ part: "path", .. })));
+        assert!(matches!(Path::from_str("/a b"), Err(ParseError::IllegalPatternError{ part: "path", .. })));
+
+        // Ports, both single and ranges
+        assert_eq!(Port::from_str("8080").unwrap(), Port::Specific(8080, TextRange::None));
+        assert_eq!(Port::from_str(":443").unwrap(), Port::Specific(443, TextRange::None));
+        assert_eq!(Port::from_str("8000-8099").unwrap(), Port::Range(8000, 8099, TextRange::None));
+        assert_eq!(Port::from_str("*").unwrap(), Port::Wildcard);
+        assert!(matches!(Port::from_str("0"), Err(ParseError::IllegalPortError{ .. })));
+        assert!(matches!(Port::from_str("65536"), Err(ParseError::IllegalPortError{ .. })));
+        assert!(matches!(Port::from_str("http"), Err(ParseError::PortParseError{ .. })));
+        assert!(matches!(Port::from_str(""), Err(ParseError::PortParseError{ .. })));
+        assert!(matches!(Port::from_str("9000-8000"), Err(ParseError::PortRangeError{ start: 9000, end: 8000, .. })));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Escapes the given string such that it can be written as a string literal.
+/// 
+/// # Arguments
+/// - `value`: The string to escape.
+/// 
+/// # Returns
+/// The escaped string, without surrounding quotes.
+fn escape_string(value: &str) -> String {
+    let mut result: String = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\'                => { result.push_str("\\\\"); },
+            '"'                 => { result.push_str("\\\""); },
+            '\n'                => { result.push_str("\\n"); },
+            '\r'                => { result.push_str("\\r"); },
+            '\t'                => { result.push_str("\\t"); },
+            c if c.is_control() => { result.push_str(&format!("\\u{{{:X}}}", c as u32)); },
+            c                   => { result.push(c); },
+        }
+    }
+    result
+}
+
+/// Checks whether the given text is a valid label of an endpoint or a path (i.e., a non-empty run of alphanumerics, underscores, dashes and percentages, or an optionally named aterisk).
+/// 
+/// # Arguments
+/// - `label`: The label to check.
+/// 
+/// # Returns
+/// True if it is valid, or false otherwise.
+fn is_valid_label(label: &str) -> bool {
+    if let Some(name) = label.strip_prefix('*') {
+        name.chars().all(|c| c.is_alphanumeric() || c == '_')
+    } else {
+        !label.is_empty() && label.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '%')
+    }
+}
+
+
+
+
+
 /***** TOPLEVEL *****/
 /// Defines a complete configuration (the root node).
 #[derive(Clone, Debug)]
@@ -24,13 +199,36 @@ pub struct Config {
     /// The proxy's rules to proxy
     pub patterns : Vec<RulesArea>,
 
+    /// The comments below the last area (or all of them, if there are no areas), including their markers, in order.
+    pub comments : Vec<String>,
     /// The range in the source text of the entire config.
-    pub range : TextRange,
+    pub range    : TextRange,
 }
 impl Node for Config {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for Config {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.config == other.config && self.patterns == other.patterns }
+}
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        // Write all areas, separated by empty lines
+        let areas = self.config.iter().map(|a| a as &dyn Display).chain(self.patterns.iter().map(|a| a as &dyn Display));
+        let mut first: bool = true;
+        for area in areas {
+            if !first { writeln!(f)?; }
+            write!(f, "{}", area)?;
+            first = false;
+        }
+
+        // Write any trailing comments after another empty line
+        if !self.comments.is_empty() && !first { writeln!(f)?; }
+        for comment in &self.comments { writeln!(f, "{}", comment)?; }
+        Ok(())
+    }
+}
 
 
 
@@ -43,13 +241,27 @@ pub struct SettingsArea {
     /// The settings in the settings area.
     pub settings : Vec<Setting>,
 
+    /// The comments directly above the area's header (including their markers), in order.
+    pub comments : Vec<String>,
     /// The range of this area.
-    pub range : TextRange,
+    pub range    : TextRange,
 }
 impl Node for SettingsArea {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for SettingsArea {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.settings == other.settings }
+}
+impl Display for SettingsArea {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for comment in &self.comments { writeln!(f, "{}", comment)?; }
+        writeln!(f, "[settings]")?;
+        for setting in &self.settings { writeln!(f, "{}", setting)?; }
+        Ok(())
+    }
+}
 
 /// Defines a single setting within the settings area.
 #[derive(Clone, Debug)]
@@ -59,13 +271,26 @@ pub struct Setting {
     /// The value of the settings
     pub value : SettingValue,
 
+    /// The comments directly above the setting (including their markers), in order.
+    pub comments : Vec<String>,
     /// The text range of the setting
-    pub range : TextRange,
+    pub range    : TextRange,
 }
 impl Node for Setting {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for Setting {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.key == other.key && self.value == other.value }
+}
+impl Display for Setting {
+    /// Writes the setting, preceded by its comments (each on their own line).
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for comment in &self.comments { writeln!(f, "{}", comment)?; }
+        write!(f, "{}: {},", self.key, self.value)
+    }
+}
 
 /// Defines a key in the setting area.
 #[derive(Clone, Debug)]
@@ -79,6 +304,14 @@ impl Node for SettingKey {
     #[inline]
     fn range(&self) -> TextRange{ self.range }
 }
+impl PartialEq for SettingKey {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.value == other.value }
+}
+impl Display for SettingKey {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}", self.value) }
+}
 
 /// Defines a value in the setting area.
 #[derive(Clone, Debug)]
@@ -111,6 +344,44 @@ impl Node for SettingValue {
         }
     }
 }
+impl PartialEq for SettingValue {
+    fn eq(&self, other: &Self) -> bool {
+        use SettingValue::*;
+        match (self, other) {
+            (String(lhs, _), String(rhs, _)) => lhs == rhs,
+            (UInt(lhs, _), UInt(rhs, _))     => lhs == rhs,
+            (SInt(lhs, _), SInt(rhs, _))     => lhs == rhs,
+            (Bool(lhs, _), Bool(rhs, _))     => lhs == rhs,
+
+            (List(lhs, _), List(rhs, _)) => lhs == rhs,
+            (Dict(lhs, _), Dict(rhs, _)) => lhs == rhs,
+
+            _ => false,
+        }
+    }
+}
+impl Display for SettingValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use SettingValue::*;
+        match self {
+            String(value, _) => write!(f, "\"{}\"", escape_string(value)),
+            UInt(value, _)   => write!(f, "{}", value),
+            SInt(value, _)   => write!(f, "{}", value),
+            Bool(value, _)   => write!(f, "{}", value),
+
+            List(values, _) => if values.is_empty() {
+                write!(f, "[]")
+            } else {
+                write!(f, "[ {} ]", values.iter().map(|v| v.to_string()).collect::<Vec<std::string::String>>().join(", "))
+            },
+            Dict(settings, _) => if settings.is_empty() {
+                write!(f, "{{}}")
+            } else {
+                write!(f, "{{ {} }}", settings.iter().map(|s| s.to_string()).collect::<Vec<std::string::String>>().join(" "))
+            },
+        }
+    }
+}
 
 
 
@@ -122,13 +393,27 @@ pub struct RulesArea {
     /// The rules within this area, if any.
     pub rules : Vec<Rule>,
 
+    /// The comments directly above the area's header (including their markers), in order.
+    pub comments : Vec<String>,
     /// The range of this area.
-    pub range : TextRange,
+    pub range    : TextRange,
 }
 impl Node for RulesArea {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for RulesArea {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.rules == other.rules }
+}
+impl Display for RulesArea {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for comment in &self.comments { writeln!(f, "{}", comment)?; }
+        writeln!(f, "[rules]")?;
+        for rule in &self.rules { writeln!(f, "{}", rule)?; }
+        Ok(())
+    }
+}
 
 /// Defines a single pattern in the list of them.
 #[derive(Clone, Debug)]
@@ -138,13 +423,26 @@ pub struct Rule {
     /// The righthand-side of the pattern (i.e., the rewriter). They are syntactically (almost) identical but semantically different.
     pub rhs : Action,
 
+    /// The comments directly above the rule (including their markers), in order.
+    pub comments : Vec<String>,
     /// The range of the entire rule.
-    pub range : TextRange,
+    pub range    : TextRange,
 }
 impl Node for Rule {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for Rule {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.lhs == other.lhs && self.rhs == other.rhs }
+}
+impl Display for Rule {
+    /// Writes the rule, preceded by its comments (each on their own line).
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        for comment in &self.comments { writeln!(f, "{}", comment)?; }
+        write!(f, "{} -> {},", self.lhs, self.rhs)
+    }
+}
 
 
 
@@ -167,6 +465,14 @@ impl Node for Pattern {
     #[inline]
     fn range(&self) -> TextRange { self.range }
 }
+impl PartialEq for Pattern {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool { self.protocol == other.protocol && self.base == other.base && self.path == other.path && self.port == other.port }
+}
+impl Display for Pattern {
+    #[inline]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult { write!(f, "{}{}{}{}", self.protocol, self.base, self.path, self.port) }
+}
 
 /// Defines what protocol the user specified in a Pattern.
 #[derive(Clone, Debug)]
@@ -180,6 +486,32 @@ impl Node for Protocol {
     #[inline]
     fn range(&self) -> TextRange { if let Self::Specific(_, range) = self { *range } else { TextRange::None } }
 }
+impl PartialEq for Protocol {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Protocol::Specific(lhs, _), Protocol::Specific(rhs, _)) => lhs == rhs,
+            (Protocol::Wildcard, Protocol::Wildcard)                 => true,
+            _                                                        => false,
+        }
+    }
+}
+impl Display for Protocol {
+    /// Writes the protocol as it prefixes a pattern (i.e., including the `://`), or nothing if it's a wildcard.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        if let Protocol::Specific(protocol, _) = self { write!(f, "{}://", protocol) } else { Ok(()) }
+    }
+}
+impl FromStr for Protocol {
+    type Err = ParseError;
+
+    /// Parses a protocol from its name (e.g., `http`, optionally followed by `://`), or `*` for a wildcard.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" { return Ok(Protocol::Wildcard); }
+        let name: &str = s.strip_suffix("://").unwrap_or(s);
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric()) { return Err(ParseError::IllegalPatternError{ part: "protocol", raw: s.into(), source: None }); }
+        Ok(Protocol::Specific(name.into(), TextRange::None))
+    }
+}
 
 /// Defines what endpoint the user specified in a Pattern.
 #[derive(Clone, Debug)]
@@ -193,18 +525,137 @@ impl Node for Endpoint {
     #[inline]
     fn range(&self) -> TextRange { if let Self::Specific(_, range) = self { *range } else { TextRange::None } }
 }
+impl PartialEq for Endpoint {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Endpoint::Specific(lhs, _), Endpoint::Specific(rhs, _)) => lhs == rhs,
+            (Endpoint::Wildcard, Endpoint::Wildcard)                 => true,
+            _                                                        => false,
+        }
+    }
+}
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Endpoint::Specific(endpoint, _) => write!(f, "{}", endpoint),
+            Endpoint::Wildcard              => write!(f, "*"),
+        }
+    }
+}
+impl FromStr for Endpoint {
+    type Err = ParseError;
+
+    /// Parses an endpoint from an IP address (where IPv6 addresses are given in brackets, e.g., `[::1]`) or a dot-separated hostname (whose labels may contain aterisks), or `*` for a wildcard.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" { return Ok(Endpoint::Wildcard); }
+        let is_ipv6 = || s.strip_prefix('[').and_then(|s| s.strip_suffix(']')).map(|ip| Ipv6Addr::from_str(ip).is_ok()).unwrap_or(false);
+        if IpAddr::from_str(s).is_err() && !is_ipv6() && !s.split('.').all(is_valid_label) { return Err(ParseError::IllegalPatternError{ part: "endpoint", raw: s.into(), source: None }); }
+        Ok(Endpoint::Specific(s.into(), TextRange::None))
+    }
+}
 
 /// Defines what path(s) the user specified in a Pattern.
 #[derive(Clone, Debug)]
 pub enum Path {
     /// It's a named one.
     Specific(Vec<String>, TextRange),
+    /// It's everything under the given path (i.e., it was written with a trailing `/*`).
+    Prefix(Vec<String>, TextRange),
     /// It's any / all.
     Wildcard,
 }
+impl Path {
+    /// Checks whether the given request path matches this Path.
+    /// 
+    /// Segments that are aterisks (e.g., `*` or `*1`) match any single segment. Empty segments (e.g., those caused by double or trailing slashes) are ignored, and any query string or fragment is not considered.
+    /// 
+    /// # Arguments
+    /// - `path`: The path of the incoming request (e.g., `/api/users?id=1`).
+    /// 
+    /// # Returns
+    /// True if the path matches, or false otherwise.
+    pub fn matches(&self, path: &str) -> bool {
+        // Split the path in its segments first
+        let path     : &str      = path.split(['?', '#']).next().unwrap_or("");
+        let segments : Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        // Match them
+        let matches = |pattern: &[String], segments: &[&str]| -> bool { pattern.iter().zip(segments.iter()).all(|(p, s)| p.starts_with('*') || p == s) };
+        match self {
+            Path::Specific(pattern, _) => pattern.len() == segments.len() && matches(pattern, &segments),
+            Path::Prefix(pattern, _)   => pattern.len() <= segments.len() && matches(pattern, &segments),
+            Path::Wildcard             => true,
+        }
+    }
+}
 impl Node for Path {
     #[inline]
-    fn range(&self) -> TextRange { if let Self::Specific(_, range) = self { *range } else { TextRange::None } }
+    fn range(&self) -> TextRange {
+        match self {
+            Path::Specific(_, range) => *range,
+            Path::Prefix(_, range)   => *range,
+            Path::Wildcard           => TextRange::None,
+        }
+    }
+}
+impl PartialEq for Path {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Path::Specific(lhs, _), Path::Specific(rhs, _)) => lhs == rhs,
+            (Path::Prefix(lhs, _), Path::Prefix(rhs, _))     => lhs == rhs,
+            (Path::Wildcard, Path::Wildcard)                 => true,
+            _                                                => false,
+        }
+    }
+}
+impl Display for Path {
+    /// Writes the path as it appears in a pattern (i.e., with a leading slash), or nothing if it's a wildcard.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        /// Writes the given segments.
+        fn write_segments(f: &mut Formatter<'_>, segments: &[String]) -> FResult {
+            for segment in segments { write!(f, "/{}", segment)?; }
+            Ok(())
+        }
+
+        match self {
+            Path::Specific(segments, _) => {
+                if segments.is_empty() { return write!(f, "/"); }
+                write_segments(f, segments)
+            },
+            Path::Prefix(segments, _) => {
+                write_segments(f, segments)?;
+                write!(f, "/*")
+            },
+            Path::Wildcard => Ok(()),
+        }
+    }
+}
+impl FromStr for Path {
+    type Err = ParseError;
+
+    /// Parses a path from its slash-separated segments (e.g., `/api/*1/info`), where a trailing `/*` makes it a prefix, or `*` for a wildcard.
+    /// 
+    /// Whitespace around segments is ignored (e.g., `/ *` is the same as `/*`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" { return Ok(Path::Wildcard); }
+        let illegal = || ParseError::IllegalPatternError{ part: "path", raw: s.into(), source: None };
+
+        // Collect the segments after the leading slash
+        let mut segments: Vec<String> = match s.strip_prefix('/') {
+            Some(rest) if rest.trim().is_empty() => vec![],
+            Some(rest)                           => rest.split('/').map(|s| s.trim().to_string()).collect(),
+            None                                 => { return Err(illegal()); },
+        };
+        if !segments.iter().all(|s| is_valid_label(s)) { return Err(illegal()); }
+
+        // An unnamed aterisk at the end turns it into a prefix
+        if segments.last().map(|s| s == "*").unwrap_or(false) {
+            segments.pop();
+            Ok(Path::Prefix(segments, TextRange::None))
+        } else {
+            Ok(Path::Specific(segments, TextRange::None))
+        }
+    }
 }
 
 /// Defines what port the user specified in a Pattern.
@@ -212,12 +663,58 @@ impl Node for Path {
 pub enum Port {
     /// It's a named one.
     Specific(u16, TextRange),
+    /// It's a contiguous range of ports (both inclusive).
+    Range(u16, u16, TextRange),
     /// It's any / all.
     Wildcard,
 }
 impl Node for Port {
     #[inline]
-    fn range(&self) -> TextRange { if let Self::Specific(_, range) = self { *range } else { TextRange::None } }
+    fn range(&self) -> TextRange {
+        match self {
+            Port::Specific(_, range) => *range,
+            Port::Range(_, _, range) => *range,
+            Port::Wildcard           => TextRange::None,
+        }
+    }
+}
+impl PartialEq for Port {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Port::Specific(lhs, _), Port::Specific(rhs, _))               => lhs == rhs,
+            (Port::Range(lstart, lend, _), Port::Range(rstart, rend, _)) => lstart == rstart && lend == rend,
+            (Port::Wildcard, Port::Wildcard)                               => true,
+            _                                                              => false,
+        }
+    }
+}
+impl Display for Port {
+    /// Writes the port as it suffixes a pattern (i.e., including the `:`), or nothing if it's a wildcard.
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Port::Specific(port, _)    => write!(f, ":{}", port),
+            Port::Range(start, end, _) => write!(f, ":{}-{}", start, end),
+            Port::Wildcard             => Ok(()),
+        }
+    }
+}
+impl FromStr for Port {
+    type Err = ParseError;
+
+    /// Parses a port number (e.g., `8080`) or a range of them (e.g., `8000-8099`), optionally prefixed by a `:`, or `*` for a wildcard.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" { return Ok(Port::Wildcard); }
+        let s: &str = s.strip_prefix(':').unwrap_or(s);
+        match s.split_once('-') {
+            Some((start, end)) => {
+                let start : u16 = parse_port_number(start, None)?;
+                let end   : u16 = parse_port_number(end, None)?;
+                if start > end { return Err(ParseError::PortRangeError{ start, end, source: None }); }
+                Ok(Port::Range(start, end, TextRange::None))
+            },
+            None => Ok(Port::Specific(parse_port_number(s, None)?, TextRange::None)),
+        }
+    }
 }
 
 
@@ -228,7 +725,7 @@ pub enum Action {
     /// Accept the given rule as-is (i.e., don't proxy but simple re-send as the original).
     Accept(TextRange),
     /// Rewrite the incoming rule to a (potentially) different one, as specified by the given pattern.
-    Rewrite(Pattern),
+    Rewrite(Box<Pattern>),
     /// Drop the incoming pattern with the given HTTP status code and message.
     Drop(u16, Option<String>, TextRange),
 }
@@ -242,3 +739,23 @@ impl Node for Action {
         }
     }
 }
+impl PartialEq for Action {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Action::Accept(_), Action::Accept(_))                         => true,
+            (Action::Rewrite(lhs), Action::Rewrite(rhs))                   => lhs == rhs,
+            (Action::Drop(lcode, lmsg, _), Action::Drop(rcode, rmsg, _)) => lcode == rcode && lmsg == rmsg,
+            _                                                              => false,
+        }
+    }
+}
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        match self {
+            Action::Accept(_)                => write!(f, "!accept"),
+            Action::Rewrite(pattern)         => write!(f, "{}", pattern),
+            Action::Drop(code, Some(msg), _) => write!(f, "!drop {} \"{}\"", code, escape_string(msg)),
+            Action::Drop(code, None, _)      => write!(f, "!drop {}", code),
+        }
+    }
+}
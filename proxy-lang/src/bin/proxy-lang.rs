@@ -0,0 +1,80 @@
+//  PROXY-LANG.rs
+//    by Lut99
+// 
+//  Created:
+//    16 Oct 2026, 18:12:40
+//  Last edited:
+//    16 Oct 2026, 18:12:40
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Entrypoint to the `proxy-lang` tool, which can be used to work with
+//!   proxy config files from the command line.
+// 
+
+use std::env;
+use std::process::ExitCode;
+
+use proxy_lang::errors::{init_colors, PrettyError as _};
+use proxy_lang::format;
+
+
+/***** CONSTANTS *****/
+/// The usage string of the tool.
+const USAGE: &str = "Usage: proxy-lang [--no-color] fmt [--check] <FILE>...";
+
+
+
+
+
+/***** ENTYRPOINT *****/
+fn main() -> ExitCode {
+    // Parse the arguments
+    let mut no_color : bool           = false;
+    let mut check    : bool           = false;
+    let mut command  : Option<String> = None;
+    let mut files    : Vec<String>    = vec![];
+    for arg in env::args().skip(1) {
+        match arg.as_str() {
+            "-h" | "--help" => { println!("{}", USAGE); return ExitCode::SUCCESS; },
+            "--no-color"    => { no_color = true; },
+            "--check"       => { check = true; },
+            _               => {
+                if command.is_none() { command = Some(arg); } else { files.push(arg); }
+            },
+        }
+    }
+    init_colors(no_color);
+    match command.as_deref() {
+        Some("fmt") => {},
+        Some(cmd)   => { eprintln!("Unknown command '{}'\n{}", cmd, USAGE); return ExitCode::FAILURE; },
+        None        => { eprintln!("{}", USAGE); return ExitCode::FAILURE; },
+    }
+    if files.is_empty() { eprintln!("{}", USAGE); return ExitCode::FAILURE; }
+
+    // Format every file
+    let mut failed: bool = false;
+    for file in files {
+        if check {
+            // Only report if the file would change
+            let source: String = match std::fs::read_to_string(&file) {
+                Ok(source) => source,
+                Err(err)   => { eprint!("{}", format::Error::FileReadError{ path: file.into(), err }.prettyprint()); failed = true; continue; },
+            };
+            match format::format_str(&file, &source) {
+                Ok(formatted) => if formatted != source { println!("Would reformat '{}'", file); failed = true; },
+                Err(err)      => { eprint!("{}", err.prettyprint()); failed = true; },
+            }
+        } else {
+            match format::format_file(&file) {
+                Ok(true)  => { println!("Formatted '{}'", file); },
+                Ok(false) => {},
+                Err(err)  => { eprint!("{}", err.prettyprint()); failed = true; },
+            }
+        }
+    }
+
+    // Done
+    if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
@@ -0,0 +1,946 @@
+//  COMPILER.rs
+//    by Lut99
+// 
+//  Created:
+//    16 Oct 2026, 18:20:11
+//  Last edited:
+//    16 Oct 2026, 19:11:37
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Compiles a parsed AST to a Config that can be used by the proxy at
+//!   runtime.
+// 
+
+use std::collections::HashMap;
+use std::env;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ipnet::IpNet;
+
+pub use crate::errors::CompileError as Error;
+use crate::spec::{Node, TextRange};
+use crate::warnings::Warning;
+use crate::ast::{self, Action, Endpoint, Path, Pattern, Port, Protocol, Rule, Setting, SettingValue};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::spec::TextPos;
+    use crate::parser::parse;
+    use crate::tests::{scan_tokens, TEST_DIR};
+    use super::*;
+
+    /// Parses the given source text to an AST Config.
+    fn parse_config(source: &str) -> ast::Config {
+        parse(&scan_tokens(source)).unwrap()
+    }
+
+    #[test]
+    fn test_compile() {
+        // Compile the example file
+        let source: String = fs::read_to_string(format!("{}/compile.pconf", TEST_DIR)).unwrap();
+        let (config, warnings): (Config, Vec<Warning>) = compile(&parse_config(&source)).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.address, IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+        assert_eq!(config.ports, vec![ 80, 443 ]);
+        assert_eq!(config.not_found_file, None);
+        assert_eq!(config.hostnames.len(), 2);
+        assert_eq!(config.hostnames.get("test.nl").map(|h| h.as_str()), Some("example.com"));
+        assert_eq!(config.hostnames.get("www.test.nl").map(|h| h.as_str()), Some("www.test.nl"));
+
+        // Compile an empty file
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_compile_settings() {
+        // Compile some settings that we can't scan (yet) from a hand-written AST
+        let setting = |key: &str, value: SettingValue| Setting{ key: ast::SettingKey{ value: key.into(), range: TextRange::None }, value, comments: vec![], range: TextRange::None };
+        let (config, _): (Config, Vec<Warning>) = compile(&ast::Config {
+            config   : vec![ ast::SettingsArea{ settings: vec![
+                setting("address", SettingValue::String("127.0.0.1".into(), TextRange::None)),
+                setting("not_found_file", SettingValue::String("/var/www/404.html".into(), TextRange::None)),
+            ], comments: vec![], range: TextRange::None } ],
+            patterns : vec![],
+            comments : vec![],
+            range    : TextRange::None,
+        }).unwrap();
+        assert_eq!(config.address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(config.not_found_file, Some(PathBuf::from("/var/www/404.html")));
+        assert_eq!(config.not_found_type, Some("text/html"));
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nnot_found_file: \"/var/www/404.json\",")).unwrap();
+        assert_eq!(config.not_found_type, Some("application/json"));
+    }
+
+    #[test]
+    fn test_compile_tls_ports() {
+        // Ports are split between HTTP and HTTPS
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80, 443, 8443 ],\ntls_ports: [ 443, 8443 ],")).unwrap();
+        assert_eq!(config.tls_ports, vec![ 443, 8443 ]);
+        assert!(!config.is_tls(80));
+        assert!(config.is_tls(443));
+        assert!(config.is_tls(8443));
+
+        // Without TLS ports, everything is plain HTTP
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80, 443 ],")).unwrap();
+        assert!(!config.is_tls(443));
+
+        // TLS ports must be a subset of the ports we listen on
+        assert!(matches!(compile(&parse_config("[settings]\nports: [ 80 ],\ntls_ports: [ 443 ],")), Err(Error::TlsPortError{ port: 443, range }) if range == TextRange::new(TextPos::new(3, 12), TextPos::new(3, 18))));
+
+        // The order of the settings does not matter
+        assert!(compile(&parse_config("[settings]\ntls_ports: [ 443 ],\nports: [ 443 ],")).is_ok());
+    }
+
+    #[test]
+    fn test_compile_cidrs() {
+        // Deny is checked before allow
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nallow_cidrs: [ \"10.0.0.0/8\", \"::1/128\" ],\ndeny_cidrs: [ \"10.0.1.0/24\" ],")).unwrap();
+        assert_eq!(config.allow_cidrs, vec![ IpNet::from_str("10.0.0.0/8").unwrap(), IpNet::from_str("::1/128").unwrap() ]);
+        assert!(config.is_allowed(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert!(config.is_allowed(IpAddr::from_str("::1").unwrap()));
+        assert!(!config.is_allowed(IpAddr::from_str("10.0.1.42").unwrap()));
+        assert!(!config.is_allowed(IpAddr::from_str("192.168.0.1").unwrap()));
+
+        // Without an allow list, everyone not denied is allowed
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\ndeny_cidrs: [ \"192.168.0.0/16\" ],")).unwrap();
+        assert!(config.is_allowed(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert!(!config.is_allowed(IpAddr::from_str("192.168.0.1").unwrap()));
+
+        // Invalid blocks are rejected at load
+        assert!(matches!(compile(&parse_config("[settings]\nallow_cidrs: [ \"10.0.0.0/33\" ],")), Err(Error::CidrParseError{ ref raw, .. }) if raw == "10.0.0.0/33"));
+        assert!(matches!(compile(&parse_config("[settings]\ndeny_cidrs: \"10.0.0.0/8\",")), Err(Error::SettingTypeError{ .. })));
+    }
+
+    #[test]
+    fn test_content_type() {
+        // Known extensions get their own type, regardless of case
+        assert_eq!(content_type(std::path::Path::new("404.html")), "text/html");
+        assert_eq!(content_type(std::path::Path::new("/var/www/404.json")), "application/json");
+        assert_eq!(content_type(std::path::Path::new("404.txt")), "text/plain");
+        assert_eq!(content_type(std::path::Path::new("404.PNG")), "image/png");
+        assert_eq!(content_type(std::path::Path::new("not.found.jpeg")), "image/jpeg");
+
+        // Anything else is HTML
+        assert_eq!(content_type(std::path::Path::new("404.unknown")), "text/html");
+        assert_eq!(content_type(std::path::Path::new("404")), "text/html");
+        assert_eq!(content_type(std::path::Path::new(".json")), "text/html");
+    }
+
+    #[test]
+    fn test_compile_warnings() {
+        // Duplicate hostnames are warned for, and the last one wins
+        let (config, warnings): (Config, Vec<Warning>) = compile(&parse_config("[rules]\ntest.nl -> example.com,\ntest.nl -> example.org,")).unwrap();
+        assert_eq!(config.hostnames.get("test.nl").map(|h| h.as_str()), Some("example.org"));
+        assert_eq!(warnings, vec![ Warning::DuplicateHostname {
+            hostname : "test.nl".into(),
+            range    : TextRange::new(TextPos::new(3, 1), TextPos::new(3, 23)),
+            prev     : TextRange::new(TextPos::new(2, 1), TextPos::new(2, 23)),
+        } ]);
+
+        // Targets that point back at the proxy are loops
+        let (_, warnings): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80, 443 ],\n[rules]\na.nl -> 127.0.0.1,\nb.nl -> localhost:443,\nc.nl -> 127.0.0.1:8080,\nd.nl -> 10.0.0.1,")).unwrap();
+        assert_eq!(warnings, vec![
+            Warning::ProxyLoop{ hostname: "a.nl".into(), target: "127.0.0.1".into(), range: TextRange::new(TextPos::new(4, 9), TextPos::new(4, 17)) },
+            Warning::ProxyLoop{ hostname: "b.nl".into(), target: "localhost:443".into(), range: TextRange::new(TextPos::new(5, 9), TextPos::new(5, 21)) },
+        ]);
+        let (_, warnings): (Config, Vec<Warning>) = compile(&parse_config("[settings]\naddress: \"10.0.0.1\",\nports: [ 80 ],\n[rules]\na.nl -> 127.0.0.1,\nb.nl -> 10.0.0.1:80,")).unwrap();
+        assert!(matches!(warnings.as_slice(), [ Warning::ProxyLoop{ ref hostname, .. } ] if hostname == "b.nl"));
+        let (_, warnings): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80 ],\n[rules]\na.nl -> [::1]:80,")).unwrap();
+        assert!(matches!(warnings.as_slice(), [ Warning::ProxyLoop{ ref hostname, .. } ] if hostname == "a.nl"));
+
+        // Duplicate ports are only listened on once
+        let (config, warnings): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80, 443, 80 ],")).unwrap();
+        assert_eq!(config.ports, vec![ 80, 443 ]);
+        assert_eq!(warnings, vec![ Warning::DuplicatePort {
+            port  : 80,
+            range : TextRange::new(TextPos::new(2, 19), TextPos::new(2, 20)),
+            prev  : TextRange::new(TextPos::new(2, 10), TextPos::new(2, 11)),
+        } ]);
+    }
+
+    #[test]
+    fn test_compile_hostnames() {
+        // Hostnames are normalized
+        assert_eq!(normalize_hostname("example.com"), "example.com");
+        assert_eq!(normalize_hostname("Example.COM"), "example.com");
+        assert_eq!(normalize_hostname("example.com."), "example.com");
+        assert_eq!(normalize_hostname("EXAMPLE.com."), "example.com");
+        assert_eq!(normalize_hostname("xn--bcher-kva.example"), "xn--bcher-kva.example");
+
+        // And so are the keys of compiled rules
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[rules]\nExample.COM -> Backend.nl,")).unwrap();
+        assert_eq!(config.hostnames.get(&normalize_hostname("example.com.")).map(|h| h.as_str()), Some("Backend.nl"));
+        let (_, warnings): (Config, Vec<Warning>) = compile(&parse_config("[rules]\nTest.nl -> example.com,\ntest.NL -> example.org,")).unwrap();
+        assert!(matches!(warnings.as_slice(), [ Warning::DuplicateHostname{ ref hostname, .. } ] if hostname == "test.nl"));
+    }
+
+    #[test]
+    fn test_describe_route() {
+        // Compiled rules remember where they came from
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[rules]\ntest.nl -> example.com,\n\nexample.com -> !accept,")).unwrap();
+        assert_eq!(config.hostname_rules.get("example.com").copied(), Some(TextRange::new(TextPos::new(4, 1), TextPos::new(4, 23))));
+        assert_eq!(config.describe_route("test.nl", "proxy.conf"), "host 'test.nl' (proxy.conf:2)");
+        assert_eq!(config.describe_route("example.com", "proxy.conf"), "host 'example.com' (proxy.conf:4)");
+
+        // Others just give the hostname
+        let mut config: Config = Config::default();
+        config.hostnames.insert("test.nl".into(), "example.com".into());
+        assert_eq!(config.describe_route("test.nl", "proxy.conf"), "host 'test.nl'");
+    }
+
+    #[test]
+    fn test_validate() {
+        // A compiled config with ports is valid
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80, 443 ],\ntls_ports: [ 443 ],\n[rules]\ntest.nl -> example.com:8080,\nexample.com -> !accept,")).unwrap();
+        assert!(config.validate().is_ok());
+
+        // The default one is not, since it doesn't listen anywhere
+        assert!(matches!(Config::default().validate().unwrap_err().as_slice(), [ Error::NoPortsError ]));
+
+        // Neither are hand-made ones with bad ports, hostnames or targets
+        let mut config: Config = Config { ports: vec![ 80 ], tls_ports: vec![ 443 ], ..Default::default() };
+        config.hostnames.insert("Test.nl".into(), "example.com".into());
+        config.hostnames.insert("a.nl".into(), "example.com:0".into());
+        config.hostnames.insert("b.nl".into(), "exa mple.com".into());
+        config.hostnames.insert("c.nl".into(), "".into());
+        config.hostnames.insert("d.nl".into(), "[::1]:8080".into());
+        let errors: Vec<Error> = config.validate().unwrap_err();
+        assert!(matches!(errors.as_slice(), [
+            Error::TlsPortError{ port: 443, .. },
+            Error::UnnormalizedHostnameError{ hostname: h1, .. },
+            Error::IllegalTargetError{ hostname: h2, .. },
+            Error::IllegalTargetError{ hostname: h3, .. },
+            Error::IllegalTargetError{ hostname: h4, .. },
+        ] if h1 == "Test.nl" && h2 == "a.nl" && h3 == "b.nl" && h4 == "c.nl"), "{:?}", errors);
+
+        // Errors about compiled hostnames point to the rule that produced them
+        let (mut config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\nports: [ 80 ],\n[rules]\ntest.nl -> example.com,")).unwrap();
+        config.hostnames.insert("test.nl".into(), "exa mple.com".into());
+        assert!(matches!(config.validate().unwrap_err().as_slice(), [ Error::IllegalTargetError{ range, .. } ] if *range == TextRange::new(TextPos::new(4, 1), TextPos::new(4, 23))));
+    }
+
+    #[test]
+    fn test_split_host() {
+        // Normal hostnames split at the colon
+        assert_eq!(split_host("example.com"), ("example.com", None));
+        assert_eq!(split_host("example.com:80"), ("example.com", Some("80")));
+
+        // IPv6 literals keep the colons inside their brackets
+        assert_eq!(split_host("[::1]"), ("::1", None));
+        assert_eq!(split_host("[::1]:8080"), ("::1", Some("8080")));
+        assert_eq!(split_host("[2001:db8::1]:443"), ("2001:db8::1", Some("443")));
+
+        // Unterminated brackets are left as-is
+        assert_eq!(split_host("[::1"), ("[::1", None));
+    }
+
+    #[test]
+    fn test_compile_rewrites() {
+        // Rewrites may change the port, the host or both
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[rules]\na.nl -> backend.nl:8080,\nb.nl -> :8443,\nc.nl -> 127.0.0.1:3000,\nd.nl -> backend.nl,\ne.nl -> !accept,")).unwrap();
+        assert_eq!(config.hostnames.get("a.nl").map(|h| h.as_str()), Some("backend.nl:8080"));
+        assert_eq!(config.hostnames.get("b.nl").map(|h| h.as_str()), Some("b.nl:8443"));
+        assert_eq!(config.hostnames.get("c.nl").map(|h| h.as_str()), Some("127.0.0.1:3000"));
+        assert_eq!(config.hostnames.get("d.nl").map(|h| h.as_str()), Some("backend.nl"));
+        assert_eq!(config.hostnames.get("e.nl").map(|h| h.as_str()), Some("e.nl"));
+
+        // IPv6 addresses are matched without brackets, but keep them in targets
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[rules]
+[::1] -> :8443,
+[2001:db8::2] -> !accept,
+a.nl -> [2001:db8::1]:8080,")).unwrap();
+        assert_eq!(config.hostnames.get("::1").map(|h| h.as_str()), Some("[::1]:8443"));
+        assert_eq!(config.hostnames.get("2001:db8::2").map(|h| h.as_str()), Some("[2001:db8::2]"));
+        assert_eq!(config.hostnames.get("a.nl").map(|h| h.as_str()), Some("[2001:db8::1]:8080"));
+
+        // Hostnames that start with an IP address are still hostnames
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[rules]
+10.0.0.1.nip.io -> b.nl,
+a.nl -> 10.0.0.1.nip.io:81,")).unwrap();
+        assert_eq!(config.hostnames.get("10.0.0.1.nip.io").map(|h| h.as_str()), Some("b.nl"));
+        assert_eq!(config.hostnames.get("a.nl").map(|h| h.as_str()), Some("10.0.0.1.nip.io:81"));
+
+        // But not to a range of ports, or to nothing at all
+        assert!(matches!(compile(&parse_config("[rules]\na.nl -> backend.nl:8000-8099,")), Err(Error::UnloweredRuleError{ .. })));
+        assert!(matches!(compile(&parse_config("[rules]\na.nl -> *,")), Err(Error::UnloweredRuleError{ .. })));
+
+        // The matched side still may not specify a port
+        assert!(matches!(compile(&parse_config("[rules]\na.nl:80 -> backend.nl,")), Err(Error::UnloweredRuleError{ .. })));
+    }
+
+    #[test]
+    fn test_compile_variables() {
+        let lookup = |name: &str| match name {
+            "HOST"  => Some("backend.nl".to_string()),
+            "EMPTY" => Some(String::new()),
+            _       => None,
+        };
+
+        // Variables are substituted, with defaults if they are unset (or empty)
+        assert_eq!(expand_vars("${HOST}", TextRange::None, &lookup).unwrap(), "backend.nl");
+        assert_eq!(expand_vars("http://${HOST}:${PORT:-8080}/", TextRange::None, &lookup).unwrap(), "http://backend.nl:8080/");
+        assert_eq!(expand_vars("${EMPTY:-default}${EMPTY}", TextRange::None, &lookup).unwrap(), "default");
+        assert_eq!(expand_vars("$HOST costs $5", TextRange::None, &lookup).unwrap(), "$HOST costs $5");
+
+        // Unset variables without a default are errors, as are malformed references
+        let range: TextRange = TextRange::new(TextPos::new(2, 10), TextPos::new(2, 20));
+        assert!(matches!(expand_vars("${UNSET}", range, &lookup), Err(Error::UnsetVariableError{ ref name, range: r }) if name == "UNSET" && r == range));
+        assert!(matches!(expand_vars("${HOST", range, &lookup), Err(Error::IllegalVariableError{ .. })));
+        assert!(matches!(expand_vars("${:-foo}", range, &lookup), Err(Error::IllegalVariableError{ .. })));
+
+        // Only string values are touched, also in nested ones
+        let (config, _): (Config, Vec<Warning>) = compile(&parse_config("[settings]\naddress: \"${PROXY_LANG_TEST_UNSET_ADDRESS:-127.0.0.1}\",")).unwrap();
+        assert_eq!(config.address, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let setting: Setting = expand_setting(&parse_config("[settings]\nhosts: [ \"${HOST}\", { name: \"${HOST}\", port: 80 } ],").config[0].settings[0], &lookup).unwrap();
+        assert_eq!(setting.key.value, "hosts");
+        assert_eq!(setting.to_string(), "hosts: [ \"backend.nl\", { name: \"backend.nl\", port: 80, } ],");
+        assert!(matches!(compile(&parse_config("[settings]\nnot_found_file: \"${PROXY_LANG_TEST_UNSET_FILE}\",")), Err(Error::UnsetVariableError{ ref name, range: TextRange::Some(TextPos{ line: 2, col: 17 }, _) }) if name == "PROXY_LANG_TEST_UNSET_FILE"));
+    }
+
+    #[test]
+    fn test_compile_errors() {
+        // Unknown settings & type mismatches
+        assert!(matches!(compile(&parse_config("[settings]\nfoo: 42,")), Err(Error::UnknownSettingError{ ref key, .. }) if key == "foo"));
+        assert!(matches!(compile(&parse_config("[settings]\nports: true,")), Err(Error::SettingTypeError{ expected: "a list of ports", got: "a boolean", .. })));
+        assert!(matches!(compile(&parse_config("[settings]\nports: [ 80, true ],")), Err(Error::SettingTypeError{ expected: "a port number", .. })));
+        assert!(matches!(compile(&parse_config("[settings]\nports: [ 99999 ],")), Err(Error::IllegalPortError{ raw: 99999, .. })));
+        assert!(matches!(compile(&parse_config("[settings]\nports: [ 0 ],")), Err(Error::IllegalPortError{ raw: 0, .. })));
+        assert!(matches!(compile(&parse_config("[settings]\nports: [ 65536 ],")), Err(Error::IllegalPortError{ raw: 65536, range: TextRange::Some(TextPos{ line: 2, col: 10 }, _) })));
+        assert_eq!(compile(&parse_config("[settings]\nports: [ 65535 ],")).unwrap().0.ports, vec![ 65535 ]);
+
+        // Rules that cannot be lowered
+        assert!(matches!(compile(&parse_config("[rules]\n*.test.nl -> example.com,")), Err(Error::UnloweredRuleError{ .. })));
+        assert!(matches!(compile(&parse_config("[rules]\nhttp://test.nl -> example.com,")), Err(Error::UnloweredRuleError{ .. })));
+        assert!(matches!(compile(&parse_config("[rules]\ntest.nl -> !drop 404,")), Err(Error::UnloweredRuleError{ .. })));
+
+        // All broken settings and rules are reported, not just the first
+        match compile(&parse_config("[settings]\nfoo: 1,\nbar: 2,\n[rules]\n*.test.nl -> example.com,\ntest.nl -> example.com,")) {
+            Err(Error::MultipleErrors{ errs }) => {
+                assert!(matches!(errs.as_slice(), [
+                    Error::UnknownSettingError{ key: k1, .. },
+                    Error::UnknownSettingError{ key: k2, .. },
+                    Error::UnloweredRuleError{ .. },
+                ] if k1 == "foo" && k2 == "bar"));
+            },
+            res => { panic!("Expected three errors, got {:?}", res); },
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Expands references to environment variables in the given string.
+/// 
+/// References are either `${NAME}`, which is replaced by the value of `NAME`, or `${NAME:-default}`, which is replaced by `default` if `NAME` is unset or empty.
+/// 
+/// # Arguments
+/// - `raw`: The string to expand.
+/// - `range`: The range of the string in the source text (used for debugging).
+/// - `lookup`: Returns the value of the environment variable with the given name, or `None` if it is not set.
+/// 
+/// # Returns
+/// The expanded string.
+/// 
+/// # Errors
+/// This function errors if a variable without default is not set, or if a reference is not terminated or has no name.
+fn expand_vars(raw: &str, range: TextRange, lookup: &impl Fn(&str) -> Option<String>) -> Result<String, Error> {
+    let mut result : String = String::with_capacity(raw.len());
+    let mut rest   : &str   = raw;
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+
+        // Find the end of the reference
+        let end: usize = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None      => { return Err(Error::IllegalVariableError{ raw: raw.into(), range }); },
+        };
+        let (name, default): (&str, Option<&str>) = match rest[start + 2..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None                  => (&rest[start + 2..end], None),
+        };
+        if name.is_empty() { return Err(Error::IllegalVariableError{ raw: raw.into(), range }); }
+
+        // Substitute it
+        match (lookup(name), default) {
+            (Some(value), None)                         => { result.push_str(&value); },
+            (Some(value), Some(_)) if !value.is_empty() => { result.push_str(&value); },
+            (_, Some(default))                          => { result.push_str(default); },
+            (None, None)                                => { return Err(Error::UnsetVariableError{ name: name.into(), range }); },
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/// Expands references to environment variables in all string values of the given setting (see `expand_vars()`).
+/// 
+/// Note that keys are left untouched.
+/// 
+/// # Arguments
+/// - `setting`: The Setting to expand.
+/// - `lookup`: Returns the value of the environment variable with the given name, or `None` if it is not set.
+/// 
+/// # Returns
+/// A copy of the Setting with its string values expanded.
+/// 
+/// # Errors
+/// This function errors if any of the string values failed to expand.
+fn expand_setting(setting: &Setting, lookup: &impl Fn(&str) -> Option<String>) -> Result<Setting, Error> {
+    /// Expands a single value.
+    fn expand_value(value: &SettingValue, lookup: &impl Fn(&str) -> Option<String>) -> Result<SettingValue, Error> {
+        match value {
+            SettingValue::String(raw, range)  => Ok(SettingValue::String(expand_vars(raw, *range, lookup)?, *range)),
+            SettingValue::List(values, range) => Ok(SettingValue::List(values.iter().map(|v| expand_value(v, lookup)).collect::<Result<Vec<SettingValue>, Error>>()?, *range)),
+            SettingValue::Dict(values, range) => Ok(SettingValue::Dict(values.iter().map(|s| expand_setting(s, lookup)).collect::<Result<Vec<Setting>, Error>>()?, *range)),
+            value                             => Ok(value.clone()),
+        }
+    }
+
+    Ok(Setting {
+        value : expand_value(&setting.value, lookup)?,
+        ..setting.clone()
+    })
+}
+
+/// Returns a human-readable description of the type of the given SettingValue.
+/// 
+/// # Arguments
+/// - `value`: The SettingValue to describe.
+/// 
+/// # Returns
+/// A static string describing the type (e.g., `a string`).
+fn value_kind(value: &SettingValue) -> &'static str {
+    use SettingValue::*;
+    match value {
+        String(_, _) => "a string",
+        UInt(_, _)   => "an unsigned integer",
+        SInt(_, _)   => "a signed integer",
+        Bool(_, _)   => "a boolean",
+
+        List(_, _) => "a list",
+        Dict(_, _) => "a dictionary",
+    }
+}
+
+/// Compiles a single SettingValue as a port number.
+/// 
+/// # Arguments
+/// - `key`: The key of the setting the value belongs to (used for debugging).
+/// - `value`: The SettingValue to compile.
+/// 
+/// # Returns
+/// The port number as a `u16`.
+/// 
+/// # Errors
+/// This function errors if the value was not an unsigned integer or out-of-range for a port (i.e., not in `1..=65535`).
+fn compile_port(key: &str, value: &SettingValue) -> Result<u16, Error> {
+    match value {
+        SettingValue::UInt(raw, range) => match u16::try_from(*raw) {
+            Ok(port) if port > 0 => Ok(port),
+            _                    => Err(Error::IllegalPortError{ raw: *raw, range: *range }),
+        },
+
+        value => Err(Error::SettingTypeError{ key: key.into(), expected: "a port number", got: value_kind(value), range: value.range() }),
+    }
+}
+
+/// Compiles a SettingValue as a list of CIDR blocks.
+/// 
+/// # Arguments
+/// - `key`: The key of the setting the value belongs to (used for debugging).
+/// - `value`: The SettingValue to compile.
+/// 
+/// # Returns
+/// The parsed CIDR blocks.
+/// 
+/// # Errors
+/// This function errors if the value was not a list of strings, or if any of them is not a valid CIDR block (e.g., `10.0.0.0/8`).
+fn compile_cidrs(key: &str, value: &SettingValue) -> Result<Vec<IpNet>, Error> {
+    let values: &[SettingValue] = match value {
+        SettingValue::List(values, _) => values,
+        value                         => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a list of CIDR blocks", got: value_kind(value), range: value.range() }); },
+    };
+    let mut cidrs: Vec<IpNet> = Vec::with_capacity(values.len());
+    for value in values {
+        match value {
+            SettingValue::String(raw, range) => match IpNet::from_str(raw) {
+                Ok(cidr) => { cidrs.push(cidr); },
+                Err(err) => { return Err(Error::CidrParseError{ raw: raw.clone(), err, range: *range }); },
+            },
+            value => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a CIDR block", got: value_kind(value), range: value.range() }); },
+        }
+    }
+    Ok(cidrs)
+}
+
+/// Compiles a single setting into the given runtime Config.
+/// 
+/// # Arguments
+/// - `config`: The Config to populate.
+/// - `setting`: The Setting to compile.
+/// - `warnings`: A list of warnings to push to if the setting is valid but suspicious.
+/// 
+/// # Errors
+/// This function errors if the setting is unknown or has an invalid value.
+fn compile_setting(config: &mut Config, setting: &Setting, warnings: &mut Vec<Warning>) -> Result<(), Error> {
+    let key: &str = setting.key.value.as_str();
+    match (key, &setting.value) {
+        ("address", SettingValue::String(raw, range)) => {
+            config.address = match IpAddr::from_str(raw) {
+                Ok(address) => address,
+                Err(err)    => { return Err(Error::IllegalAddressError{ raw: raw.clone(), err, range: *range }); },
+            };
+        },
+        ("address", value) => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a string", got: value_kind(value), range: value.range() }); },
+
+        ("ports", SettingValue::List(values, _)) => {
+            // Deduplicate the ports, since the proxy can only listen on each of them once
+            let mut seen: HashMap<u16, TextRange> = HashMap::new();
+            config.ports = vec![];
+            for value in values {
+                let port: u16 = compile_port(key, value)?;
+                if let Some(prev) = seen.get(&port) {
+                    warnings.push(Warning::DuplicatePort{ port, range: value.range(), prev: *prev });
+                    continue;
+                }
+                seen.insert(port, value.range());
+                config.ports.push(port);
+            }
+        },
+        ("ports", value) => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a list of ports", got: value_kind(value), range: value.range() }); },
+
+        ("not_found_file", SettingValue::String(raw, _)) => {
+            let path: PathBuf = raw.into();
+            config.not_found_type = Some(content_type(&path));
+            config.not_found_file = Some(path);
+        },
+        ("not_found_file", value) => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a string", got: value_kind(value), range: value.range() }); },
+
+        ("tls_ports", SettingValue::List(values, _)) => {
+            config.tls_ports = vec![];
+            for value in values {
+                let port: u16 = compile_port(key, value)?;
+                if !config.tls_ports.contains(&port) { config.tls_ports.push(port); }
+            }
+        },
+        ("tls_ports", value) => { return Err(Error::SettingTypeError{ key: key.into(), expected: "a list of ports", got: value_kind(value), range: value.range() }); },
+
+        ("allow_cidrs", value) => { config.allow_cidrs = compile_cidrs(key, value)?; },
+        ("deny_cidrs", value)  => { config.deny_cidrs = compile_cidrs(key, value)?; },
+
+        (_, _) => { return Err(Error::UnknownSettingError{ key: key.into(), range: setting.key.range }); },
+    }
+
+    // Done
+    Ok(())
+}
+
+
+
+/// Lowers the given pattern to a single hostname.
+/// 
+/// # Arguments
+/// - `pattern`: The Pattern to lower.
+/// - `what`: Describes the role of the pattern in the rule (used for debugging).
+/// 
+/// # Returns
+/// The hostname that this pattern matches or rewrites to. IPv6 addresses are returned without their brackets, like `split_host()` does for incoming hostnames.
+/// 
+/// # Errors
+/// This function errors if the pattern matches more than a single hostname (i.e., it has wildcards), or if it specifies parts that a hostname mapping cannot represent.
+fn lower_host(pattern: &Pattern, what: &str) -> Result<String, Error> {
+    if let Protocol::Specific(protocol, range) = &pattern.protocol {
+        return Err(Error::UnloweredRuleError{ reason: format!("{} specifies protocol '{}', but hostname mappings are protocol-agnostic", what, protocol), range: *range });
+    }
+    if let Path::Specific(_, range) | Path::Prefix(_, range) = &pattern.path {
+        return Err(Error::UnloweredRuleError{ reason: format!("{} specifies a path, but hostname mappings only consider hostnames", what), range: *range });
+    }
+    match &pattern.port {
+        Port::Specific(port, range)    => { return Err(Error::UnloweredRuleError{ reason: format!("{} specifies port {}, but hostname mappings only consider hostnames", what, port), range: *range }); },
+        Port::Range(start, end, range) => { return Err(Error::UnloweredRuleError{ reason: format!("{} specifies ports {}-{}, but hostname mappings only consider hostnames", what, start, end), range: *range }); },
+        Port::Wildcard                 => {},
+    }
+    match &pattern.base {
+        Endpoint::Specific(host, range) => {
+            if host.contains('*') { return Err(Error::UnloweredRuleError{ reason: format!("{} '{}' has wildcards, but hostname mappings need a concrete hostname", what, host), range: *range }); }
+            Ok(host.strip_prefix('[').and_then(|host| host.strip_suffix(']')).unwrap_or(host).into())
+        },
+        Endpoint::Wildcard => Err(Error::UnloweredRuleError{ reason: format!("{} does not specify a hostname", what), range: pattern.range }),
+    }
+}
+
+/// Lowers the given pattern to a target that the proxy can connect to.
+/// 
+/// The target is a hostname, optionally followed by a port (i.e., `host` or `host:port`). If the pattern only specifies a port, the matched hostname is kept.
+/// 
+/// # Arguments
+/// - `pattern`: The Pattern to lower.
+/// - `from`: The hostname that is matched by the rule (used if the pattern does not specify one).
+/// 
+/// # Returns
+/// The target that the rule rewrites to.
+/// 
+/// # Errors
+/// This function errors if the pattern has wildcards or a port range, if it specifies parts that a hostname mapping cannot represent or if it specifies neither a hostname nor a port.
+fn lower_target(pattern: &Pattern, from: &str) -> Result<String, Error> {
+    if let Protocol::Specific(protocol, range) = &pattern.protocol {
+        return Err(Error::UnloweredRuleError{ reason: format!("Target pattern specifies protocol '{}', but hostname mappings are protocol-agnostic", protocol), range: *range });
+    }
+    if let Path::Specific(_, range) | Path::Prefix(_, range) = &pattern.path {
+        return Err(Error::UnloweredRuleError{ reason: "Target pattern specifies a path, but hostname mappings only consider hostnames".into(), range: *range });
+    }
+
+    // Resolve the host, then add the port (if any)
+    let host: &str = match &pattern.base {
+        Endpoint::Specific(host, range) => {
+            if host.contains('*') { return Err(Error::UnloweredRuleError{ reason: format!("Target pattern '{}' has wildcards, but hostname mappings need a concrete hostname", host), range: *range }); }
+            host
+        },
+        Endpoint::Wildcard => {
+            if matches!(pattern.port, Port::Wildcard) { return Err(Error::UnloweredRuleError{ reason: "Target pattern specifies neither a hostname nor a port".into(), range: pattern.range }); }
+            from
+        },
+    };
+    match &pattern.port {
+        Port::Specific(port, _)        => Ok(format!("{}:{}", host, port)),
+        Port::Range(start, end, range) => Err(Error::UnloweredRuleError{ reason: format!("Target pattern specifies ports {}-{}, but a rule can only rewrite to a single port", start, end), range: *range }),
+        Port::Wildcard                 => Ok(host.into()),
+    }
+}
+
+/// Lowers a single rule to a hostname mapping.
+/// 
+/// # Arguments
+/// - `rule`: The Rule to lower.
+/// 
+/// # Returns
+/// A tuple of the hostname that is matched and the target it maps to (either `host` or `host:port`).
+/// 
+/// # Errors
+/// This function errors if the rule cannot be lowered to a `host -> host[:port]` mapping.
+fn lower_rule(rule: &Rule) -> Result<(String, String), Error> {
+    // Resolve both sides of the rule
+    let from : String = normalize_hostname(&lower_host(&rule.lhs, "Matched pattern")?);
+    let host : String = if from.contains(':') { format!("[{}]", from) } else { from.clone() };
+    let to   : String = match &rule.rhs {
+        Action::Accept(_)         => host,
+        Action::Rewrite(pattern)  => lower_target(pattern, &host)?,
+        Action::Drop(_, _, range) => { return Err(Error::UnloweredRuleError{ reason: "dropping requests is not supported by hostname mappings".into(), range: *range }); },
+    };
+
+    // Done
+    Ok((from, to))
+}
+
+/// Checks whether the given target is a well-formed `host` or `host:port`.
+/// 
+/// # Arguments
+/// - `target`: The target to check.
+/// 
+/// # Returns
+/// True if the host is non-empty and only consists of characters that may appear in a hostname or IP address, and the port (if any) is in `1..=65535`.
+fn is_valid_target(target: &str) -> bool {
+    let (host, port): (&str, Option<&str>) = split_host(target);
+    if host.is_empty() || !host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_' || c == ':') { return false; }
+    match port {
+        Some(port) => matches!(u16::from_str(port), Ok(port) if port > 0),
+        None       => true,
+    }
+}
+
+/// Checks whether the given target refers back to the proxy itself.
+/// 
+/// This only considers literal addresses (and `localhost`), since we don't resolve hostnames at load time.
+/// 
+/// # Arguments
+/// - `config`: The Config with the address and ports that the proxy listens on.
+/// - `target`: The target to check (either `host` or `host:port`). If it has no port, the incoming port is kept, which is always one we listen on.
+/// 
+/// # Returns
+/// True if connecting to the target would connect to the proxy itself.
+fn is_loop(config: &Config, target: &str) -> bool {
+    if config.ports.is_empty() { return false; }
+
+    // Check the port first
+    let (host, port): (&str, Option<&str>) = split_host(target);
+    if let Some(port) = port {
+        match u16::from_str(port) {
+            Ok(port) if config.ports.contains(&port) => {},
+            _                                        => { return false; },
+        }
+    }
+
+    // Then the address
+    let ip: IpAddr = if host.eq_ignore_ascii_case("localhost") {
+        IpAddr::V4(Ipv4Addr::LOCALHOST)
+    } else {
+        match IpAddr::from_str(host) {
+            Ok(ip) => ip,
+            Err(_) => { return false; },
+        }
+    };
+    ip == config.address || (config.address.is_unspecified() && (ip.is_loopback() || ip.is_unspecified()))
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Defines the configuration of the proxy as it is used at runtime.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// The address on which the proxy listens.
+    pub address        : IpAddr,
+    /// The ports on which the proxy listens.
+    pub ports          : Vec<u16>,
+    /// The subset of `ports` on which the proxy terminates TLS. The others serve plain HTTP.
+    pub tls_ports      : Vec<u16>,
+    /// The file to serve when a hostname is not found, if any.
+    pub not_found_file : Option<PathBuf>,
+    /// The `Content-Type` of the `not_found_file`, if any (see `content_type()`).
+    pub not_found_type : Option<&'static str>,
+    /// The client addresses that are allowed to use the proxy. If empty, all clients that are not denied are allowed.
+    pub allow_cidrs    : Vec<IpNet>,
+    /// The client addresses that are denied from using the proxy. Takes precedence over `allow_cidrs`.
+    pub deny_cidrs     : Vec<IpNet>,
+
+    /// Maps incoming hostnames to the targets to proxy them to, which are either `host` or `host:port`. The keys are normalized (see `normalize_hostname()`), so incoming hostnames should be too before looking them up.
+    pub hostnames      : HashMap<String, String>,
+    /// The range of the rule that produced every entry in `hostnames`, for debugging which rule matched. Hostnames that were not compiled from source text have no entry.
+    pub hostname_rules : HashMap<String, TextRange>,
+}
+
+impl Default for Config {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            address        : IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            ports          : vec![],
+            tls_ports      : vec![],
+            not_found_file : None,
+            not_found_type : None,
+            allow_cidrs    : vec![],
+            deny_cidrs     : vec![],
+
+            hostnames      : HashMap::new(),
+            hostname_rules : HashMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Checks whether the proxy should terminate TLS on the given port.
+    /// 
+    /// # Arguments
+    /// - `port`: The port to check.
+    /// 
+    /// # Returns
+    /// True if the port serves HTTPS, or false if it serves plain HTTP.
+    #[inline]
+    pub fn is_tls(&self, port: u16) -> bool { self.tls_ports.contains(&port) }
+
+    /// Checks whether the given client is allowed to use the proxy.
+    /// 
+    /// Clients are first checked against `deny_cidrs`, then against `allow_cidrs` (where an empty list allows everyone).
+    /// 
+    /// # Arguments
+    /// - `ip`: The address of the client.
+    /// 
+    /// # Returns
+    /// True if the client may be proxied, or false if it should be blocked.
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        if self.deny_cidrs.iter().any(|cidr| cidr.contains(&ip)) { return false; }
+        self.allow_cidrs.is_empty() || self.allow_cidrs.iter().any(|cidr| cidr.contains(&ip))
+    }
+
+    /// Describes the route for the given hostname for use in logs, e.g., `host 'example.com' (proxy.conf:12)`.
+    /// 
+    /// # Arguments
+    /// - `hostname`: The (normalized) hostname that was matched.
+    /// - `name`: The name of the file that this Config was compiled from.
+    /// 
+    /// # Returns
+    /// A short description of the hostname and the line of the rule that matched it. If the rule has no position in the source text, only the hostname is given.
+    pub fn describe_route(&self, hostname: &str, name: &str) -> String {
+        match self.hostname_rules.get(hostname).and_then(|range| range.start()) {
+            Some(start) => format!("host '{}' ({}:{})", hostname, name, start.line),
+            None        => format!("host '{}'", hostname),
+        }
+    }
+
+    /// Checks that this Config makes sense to run the proxy with.
+    /// 
+    /// `compile()` already guarantees most of this, but a Config may also be built or modified by hand. Note that an empty Config (e.g., `Config::default()`) is not valid, since it listens on no ports.
+    /// 
+    /// # Errors
+    /// This function errors with every problem found, i.e., if the proxy listens on no ports, if a TLS port is not one of the listened ports, if a hostname is not normalized (see `normalize_hostname()`) or if a target is not a valid `host` or `host:port`.
+    pub fn validate(&self) -> Result<(), Vec<Error>> {
+        let mut errors: Vec<Error> = vec![];
+
+        // Check the ports
+        if self.ports.is_empty() { errors.push(Error::NoPortsError); }
+        for port in &self.tls_ports {
+            if !self.ports.contains(port) { errors.push(Error::TlsPortError{ port: *port, range: TextRange::None }); }
+        }
+
+        // Check the hostnames (in order, to report errors deterministically)
+        let mut hostnames: Vec<(&String, &String)> = self.hostnames.iter().collect();
+        hostnames.sort();
+        for (hostname, target) in hostnames {
+            let range: TextRange = self.hostname_rules.get(hostname).copied().unwrap_or(TextRange::None);
+            if *hostname != normalize_hostname(hostname) { errors.push(Error::UnnormalizedHostnameError{ hostname: hostname.clone(), range }); }
+            if !is_valid_target(target) { errors.push(Error::IllegalTargetError{ hostname: hostname.clone(), target: target.clone(), range }); }
+        }
+
+        // Done
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+
+
+/// Determines the `Content-Type` of the given file based on its extension.
+/// 
+/// Extensions are matched case-insensitively. Files with an unknown (or without an) extension are assumed to be HTML.
+/// 
+/// # Arguments
+/// - `path`: The path of the file.
+/// 
+/// # Returns
+/// The MIME type to send as the file's `Content-Type`.
+pub fn content_type(path: &std::path::Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()).map(|ext| ext.to_ascii_lowercase()).as_deref() {
+        Some("html") | Some("htm") => "text/html",
+        Some("txt")                => "text/plain",
+        Some("css")                => "text/css",
+        Some("js")                 => "text/javascript",
+        Some("json")               => "application/json",
+        Some("xml")                => "application/xml",
+        Some("png")                => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif")                => "image/gif",
+        Some("svg")                => "image/svg+xml",
+        Some("webp")               => "image/webp",
+        Some("ico")                => "image/x-icon",
+
+        _ => "text/html",
+    }
+}
+
+/// Normalizes the given hostname such that equivalent hostnames compare equal.
+/// 
+/// Concretely, this lowercases the hostname and strips a single trailing dot (i.e., the root of a fully-qualified domain name). Internationalized hostnames are expected in their punycode (`xn--`) form, which is left untouched.
+/// 
+/// # Arguments
+/// - `host`: The hostname to normalize.
+/// 
+/// # Returns
+/// The normalized hostname.
+pub fn normalize_hostname(host: &str) -> String {
+    host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase()
+}
+
+/// Splits the value of a `Host` header into the hostname and the port, if any.
+/// 
+/// IPv6 literals are given in brackets (e.g., `[::1]:8080`), in which case the brackets are stripped from the returned hostname. The hostname is not normalized; use `normalize_hostname()` for that.
+/// 
+/// # Arguments
+/// - `host`: The value of the `Host` header.
+/// 
+/// # Returns
+/// A tuple of the hostname and the (unparsed) port, if one was given.
+pub fn split_host(host: &str) -> (&str, Option<&str>) {
+    // Take IPv6 literals up to the closing bracket
+    if let Some(rest) = host.strip_prefix('[') {
+        if let Some(end) = rest.find(']') {
+            return (&rest[..end], rest[end + 1..].strip_prefix(':'));
+        }
+        return (host, None);
+    }
+
+    // Otherwise, split at the first colon
+    match host.split_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None               => (host, None),
+    }
+}
+
+
+
+/// Compiles the given AST to a runtime Config.
+/// 
+/// Settings that are not given keep their default value (see `Config::default()`). The parser already rejects settings that are given multiple times, but if a hand-written AST does so anyway, then the last one wins. If a hostname is mapped by multiple rules, then the last one wins too (and a warning is emitted).
+/// 
+/// Broken settings and rules are skipped, so that the errors in all of them are reported in one go.
+/// 
+/// # Arguments
+/// - `config`: The root node of the AST to compile.
+/// 
+/// # Returns
+/// A new Config that can be used by the proxy, together with a list of non-fatal warnings encountered during compilation.
+/// 
+/// # Errors
+/// This function errors if a setting is unknown or has an invalid value, if a TLS port is not one of the listened ports, or if a rule cannot be lowered to a `host -> host` mapping. If multiple errors occurred, then they are returned as a `CompileError::MultipleErrors`.
+pub fn compile(config: &ast::Config) -> Result<(Config, Vec<Warning>), Error> {
+    let mut result   : Config       = Config::default();
+    let mut warnings : Vec<Warning> = vec![];
+    let mut errors   : Vec<Error>   = vec![];
+
+    // Compile the settings first, remembering where the TLS ports were given
+    let mut tls_range: TextRange = TextRange::None;
+    for area in &config.config {
+        for setting in &area.settings {
+            let setting: Setting = match expand_setting(setting, &|name| env::var(name).ok()) {
+                Ok(setting) => setting,
+                Err(err)    => { errors.push(err); continue; },
+            };
+            if let Err(err) = compile_setting(&mut result, &setting, &mut warnings) { errors.push(err); continue; }
+            if setting.key.value == "tls_ports" { tls_range = setting.value.range(); }
+        }
+    }
+
+    // TLS can only be terminated on ports we listen on (which we only know for sure if all settings compiled)
+    if errors.is_empty() {
+        for port in &result.tls_ports {
+            if !result.ports.contains(port) { errors.push(Error::TlsPortError{ port: *port, range: tls_range }); }
+        }
+    }
+
+    // Then the rules
+    for area in &config.patterns {
+        for rule in &area.rules {
+            let (from, to): (String, String) = match lower_rule(rule) {
+                Ok(mapping) => mapping,
+                Err(err)    => { errors.push(err); continue; },
+            };
+            if let Some(prev) = result.hostname_rules.insert(from.clone(), rule.range) {
+                warnings.push(Warning::DuplicateHostname{ hostname: from.clone(), range: rule.range, prev });
+            }
+            if is_loop(&result, &to) {
+                warnings.push(Warning::ProxyLoop{ hostname: from.clone(), target: to.clone(), range: rule.rhs.range() });
+            }
+            result.hostnames.insert(from, to);
+        }
+    }
+
+    // Done
+    match errors.len() {
+        0 => Ok((result, warnings)),
+        1 => Err(errors.pop().unwrap()),
+        _ => Err(Error::MultipleErrors{ errs: errors }),
+    }
+}
@@ -4,7 +4,7 @@
 //  Created:
 //    07 Oct 2022, 21:50:04
 //  Last edited:
-//    22 Oct 2022, 14:49:29
+//    16 Oct 2026, 19:11:37
 //  Auto updated?
 //    Yes
 // 
@@ -15,24 +15,153 @@
 //!   for the program.
 // 
 
+use std::env;
 use std::error::Error;
+use std::ffi::OsString;
 use std::fmt::{Display, Formatter, Result as FResult};
+use std::path::PathBuf;
 
 use console::{style, Style};
 
-use crate::spec::Node;
-use crate::source::SourceText;
+use crate::spec::TextRange;
+use crate::source::{SourceRef, SourceText};
 use crate::tokens::{Token, TokenList};
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::scanner::scan;
+    use super::*;
+
+    #[test]
+    fn test_no_color() {
+        // Check when NO_COLOR asks us to disable colors
+        assert!(!no_color_requested(None));
+        assert!(!no_color_requested(Some("".into())));
+        assert!(no_color_requested(Some("1".into())));
+        assert!(no_color_requested(Some("false".into())));
+
+        // Render an error without colors
+        init_colors(true);
+        let err: ScanError = scan("<test>", "name: \"unterminated".as_bytes()).unwrap_err();
+        let text: String = format!("{}", err.prettyprint());
+        assert!(text.starts_with("error[E0102]: Unterminated string literal"));
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_codes() {
+        use std::collections::HashSet;
+
+        // Construct one of every error
+        let int_err = || "x".parse::<u8>().unwrap_err();
+        let scan_errs: Vec<ScanError> = vec![
+            ScanError::ReaderReadError{ file: "<test>".into(), err: std::io::Error::other("test") },
+            ScanError::UnterminatedStringError{ source: crate::source::SourceRef::new("<test>", "\"").into() },
+            ScanError::ScanError{ err: "test".into() },
+            ScanError::Utf8Error{ file: "<test>".into(), offset: 0 },
+        ];
+        let parse_errs: Vec<ParseError> = vec![
+            ParseError::EofError{ expected: Box::new(Token::Comma(None)) },
+            ParseError::UnexpectedTokenError{ got: Box::new(Token::Colon(None)), expected: Box::new(Token::Comma(None)) },
+            ParseError::NonEmptyTokenList{ remain: vec![] },
+            ParseError::UIntParseError{ raw: "x".into(), err: int_err(), source: None },
+            ParseError::SIntParseError{ raw: "x".into(), err: int_err(), source: None },
+            ParseError::BoolParseError{ raw: "x".into(), source: None },
+            ParseError::PortParseError{ raw: "x".into(), err: int_err(), source: None },
+            ParseError::IllegalPortError{ raw: "0".into(), source: None },
+            ParseError::PortRangeError{ start: 2, end: 1, source: None },
+            ParseError::StatusCodeParseError{ raw: "x".into(), err: int_err(), source: None },
+            ParseError::IllegalStatusCodeError{ status: 600, source: None },
+            ParseError::IllegalPatternError{ part: "path", raw: "x".into(), source: None },
+            ParseError::UnknownActionError{ raw: "x".into(), source: None },
+            ParseError::NestingDepthError{ max: 1, source: None },
+            ParseError::DuplicateSettingError{ key: "x".into(), range: TextRange::None, prev: TextRange::None },
+            ParseError::DuplicateSectionError{ section: "rules", range: TextRange::None, prev: TextRange::None },
+            ParseError::SectionOrderError{ range: TextRange::None, rules: TextRange::None },
+            ParseError::NomError{ errs: vec![] },
+            ParseError::MultipleErrors{ errs: vec![] },
+        ];
+
+        let compile_errs: Vec<CompileError> = vec![
+            CompileError::UnknownSettingError{ key: "x".into(), range: TextRange::None },
+            CompileError::SettingTypeError{ key: "x".into(), expected: "x", got: "x", range: TextRange::None },
+            CompileError::IllegalAddressError{ raw: "x".into(), err: "x".parse::<std::net::IpAddr>().unwrap_err(), range: TextRange::None },
+            CompileError::IllegalPortError{ raw: 0, range: TextRange::None },
+            CompileError::CidrParseError{ raw: "x".into(), err: "x".parse::<ipnet::IpNet>().unwrap_err(), range: TextRange::None },
+            CompileError::TlsPortError{ port: 443, range: TextRange::None },
+            CompileError::UnloweredRuleError{ reason: "x".into(), range: TextRange::None },
+            CompileError::UnsetVariableError{ name: "x".into(), range: TextRange::None },
+            CompileError::IllegalVariableError{ raw: "x".into(), range: TextRange::None },
+            CompileError::NoPortsError,
+            CompileError::UnnormalizedHostnameError{ hostname: "x".into(), range: TextRange::None },
+            CompileError::IllegalTargetError{ hostname: "x".into(), target: "x".into(), range: TextRange::None },
+            CompileError::MultipleErrors{ errs: vec![] },
+        ];
+
+        // Assert the codes are unique and non-empty
+        let codes: Vec<&'static str> = scan_errs.iter().map(|e| e.code()).chain(parse_errs.iter().map(|e| e.code())).chain(compile_errs.iter().map(|e| e.code())).collect();
+        assert!(codes.iter().all(|c| !c.is_empty()));
+        assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+        assert_eq!(ParseError::EofError{ expected: Box::new(Token::Comma(None)) }.code(), "E0001");
+
+        // They're shown in the pretty-printed output
+        init_colors(true);
+        assert!(format!("{}", parse_errs[1].prettyprint()).starts_with("error[E0002]: "));
+        assert!(format!("{}", compile_errs[9].prettyprint()).starts_with("error[E0210]: "));
+    }
+
+    #[test]
+    fn test_prettyprint_with() {
+        init_colors(true);
+        let source: &str = "[settings]\nfoo: 1,\nbar: 2,\n";
+        let err: CompileError = crate::compiler::compile(&crate::loader::load("<test>", source).unwrap()).unwrap_err();
+
+        // Without the source text, only the positions are known
+        assert_eq!(format!("{}", err.prettyprint()), concat!(
+            "error[E0201]: Unknown setting 'foo'\n",
+            " --> line 2, column 1\n",
+            "\n",
+            "error[E0201]: Unknown setting 'bar'\n",
+            " --> line 3, column 1\n",
+            "\n",
+        ));
+
+        // With it, every error gets an excerpt
+        assert_eq!(format!("{}", err.prettyprint_with("<test>", source)), concat!(
+            "error[E0201]: Unknown setting 'foo'\n",
+            " --> <test>:2:1\n",
+            "  |\n",
+            "2 | foo: 1,\n",
+            "  | ^^^\n",
+            "\n",
+            "error[E0201]: Unknown setting 'bar'\n",
+            " --> <test>:3:1\n",
+            "  |\n",
+            "3 | bar: 2,\n",
+            "  | ^^^\n",
+            "\n",
+        ));
+
+        // Errors without a position fall back to the plain version
+        let err: CompileError = CompileError::NoPortsError;
+        assert_eq!(format!("{}", err.prettyprint_with("<test>", source)), format!("{}", err.prettyprint()));
+    }
+}
+
+
+
+
+
 /***** HELPER MACROS *****/
-/// Prints 'error: ' with proper formatting.
+/// Prints 'error[<code>]: ' with proper formatting.
 macro_rules! error {
-    ($f:ident) => {
-        write!($f, "{}: ", style("error").red().bold())
+    ($f:ident, $code:expr) => {
+        write!($f, "{}: ", style(format!("error[{}]", $code)).red().bold())
     };
-    ($f:ident, $fmt:literal, $($t:tt)*) => {
-        write!($f, concat!("{}: ", $fmt), style("error").red().bold(), $($t)*)
+    ($f:ident, $code:expr, $fmt:literal, $($t:tt)*) => {
+        write!($f, concat!("{}: ", $fmt), style(format!("error[{}]", $code)).red().bold(), $($t)*)
     };
 }
 
@@ -40,17 +169,39 @@ macro_rules! error {
 
 
 
+/***** HELPER FUNCTIONS *****/
+/// Checks whether the given value of the `NO_COLOR` environment variable asks us to disable colors.
+/// 
+/// Per <https://no-color.org>, this is the case if it is set to any non-empty value.
+/// 
+/// # Arguments
+/// - `value`: The value of the `NO_COLOR` environment variable, or `None` if it is not set.
+/// 
+/// # Returns
+/// True if colors should be disabled, or false otherwise.
+#[inline]
+fn no_color_requested(value: Option<OsString>) -> bool { value.map(|v| !v.is_empty()).unwrap_or(false) }
+
+
+
+
+
 /***** AUXILLARY *****/
 /// Defines a helper struct that can pretty print the given error.
 #[derive(Debug)]
 pub struct ErrorPrettyPrinter<'a> {
     /// The error to pretty print.
-    err : &'a dyn PrettyError,
+    err    : &'a dyn PrettyError,
+    /// The source text that the error was produced for, if known.
+    source : Option<SourceRef<'a>>,
 }
 
 impl<'a> Display for ErrorPrettyPrinter<'a> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
-        self.err.prettyprint_fmt(f)
+        match &self.source {
+            Some(source) => self.err.prettyprint_fmt_with(source, f),
+            None         => self.err.prettyprint_fmt(f),
+        }
     }
 }
 
@@ -86,6 +237,18 @@ pub trait PrettyError: Error {
     /// This function errors if we failed to write somehow. Any other errors should probably be panics, at this point (or handled gracefully).
     fn prettyprint_multiple(&self, _f: &mut Formatter<'_>) -> FResult { Ok(()) }
 
+    /// Prettyprints the error with an excerpt of the given source text.
+    /// 
+    /// This is meant for errors that only know where in the source text they occurred (e.g., compile errors), not what it looks like. By default, the source text is ignored and `prettyprint_fmt()` is called.
+    /// 
+    /// # Arguments
+    /// - `source`: The (whole) source text that the error was produced for.
+    /// - `f`: The Formatter to write to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write somehow. Any other errors should probably be panics, at this point (or handled gracefully).
+    fn prettyprint_fmt_with(&self, _source: &SourceRef, f: &mut Formatter<'_>) -> FResult { self.prettyprint_fmt(f) }
+
 
 
     // Global
@@ -96,7 +259,24 @@ pub trait PrettyError: Error {
     #[inline]
     fn prettyprint<'a>(&'a self) -> ErrorPrettyPrinter<'a> where Self: Sized {
         ErrorPrettyPrinter {
-            err : self,
+            err    : self,
+            source : None,
+        }
+    }
+
+    /// Returns an ErrorPrettyPrint object that prettyprints this error with an excerpt of the source text it was produced for.
+    /// 
+    /// # Arguments
+    /// - `name`: Some name / path that the user can use to identify the source text.
+    /// - `source`: The (whole) source text that the error was produced for.
+    /// 
+    /// # Returns
+    /// An ErrorPrettyPrint object that implements Display.
+    #[inline]
+    fn prettyprint_with<'a>(&'a self, name: &'a str, source: &'a str) -> ErrorPrettyPrinter<'a> where Self: Sized {
+        ErrorPrettyPrinter {
+            err    : self,
+            source : Some(SourceRef::new(name, source)),
         }
     }
 
@@ -111,6 +291,7 @@ pub trait PrettyError: Error {
         // Try them all, in-order
         self.prettyprint_plain(f)?;
         self.prettyprint_source(f)?;
+        self.prettyprint_multiple(f)?;
 
         // Done
         Ok(())
@@ -122,21 +303,90 @@ pub trait PrettyError: Error {
 
 
 /***** LIBRARY *****/
+/// Decides whether errors and warnings (and the source text they point to) are rendered with colors.
+/// 
+/// Colors are disabled if `no_color` is true (e.g., because the user gave `--no-color`) or if the `NO_COLOR` environment variable is set to a non-empty value. Otherwise, `console`'s own detection is kept, which already disables colors if we are not writing to a terminal.
+/// 
+/// # Arguments
+/// - `no_color`: Whether to disable colors regardless of the environment.
+pub fn init_colors(no_color: bool) {
+    if no_color || no_color_requested(env::var_os("NO_COLOR")) {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+}
+
+
+
+/// Defines the error that occurs when a SourceRef is enlarged beyond the end of its source text.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct OutOfBoundsError {
+    /// The offset (in bytes) of the SourceRef in its source text.
+    pub offset : usize,
+    /// The size (in bytes) of the SourceRef before enlarging.
+    pub size   : usize,
+    /// The number of bytes we attempted to enlarge it with.
+    pub n      : usize,
+    /// The length (in bytes) of the source text.
+    pub len    : usize,
+}
+
+impl Display for OutOfBoundsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        write!(f, "Enlarging a SourceRef with offset {} and size {} by {} bytes overflows its source text of {} bytes", self.offset, self.size, self.n, self.len)
+    }
+}
+
+impl Error for OutOfBoundsError {}
+
+
+
 /// Defines errors that may occur during scanning.
+/// 
+/// Every error has a stable code (see `ScanError::code()`), which is also shown when it is pretty-printed:
+/// 
+/// | Code    | Variant                   |
+/// |---------|---------------------------|
+/// | `E0101` | `ReaderReadError`         |
+/// | `E0102` | `UnterminatedStringError` |
+/// | `E0103` | `ScanError`               |
+/// | `E0104` | `Utf8Error`               |
 #[derive(Debug)]
 pub enum ScanError {
     /// Failed to read the given reader as source text.
     ReaderReadError{ file: String, err: std::io::Error },
+    /// The given reader is not valid UTF-8.
+    Utf8Error{ file: String, offset: usize },
+    /// A string literal is never closed
+    UnterminatedStringError{ source: SourceText },
     /// Failed to scan (nom error)
     ScanError{ err: String },
 }
 
+impl ScanError {
+    /// Returns the stable, machine-readable code of this error.
+    /// 
+    /// # Returns
+    /// A code of the shape `E01xx`. Codes are never reused for other errors.
+    pub fn code(&self) -> &'static str {
+        use self::ScanError::*;
+        match self {
+            ReaderReadError{ .. }         => "E0101",
+            UnterminatedStringError{ .. } => "E0102",
+            ScanError{ .. }               => "E0103",
+            Utf8Error{ .. }               => "E0104",
+        }
+    }
+}
+
 impl Display for ScanError {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use self::ScanError::*;
         match self {
-            ReaderReadError{ file, err } => write!(f, "Failed to read from input '{}': {}", file, err),
-            ScanError{ err }             => write!(f, "Syntax error: {}", err),
+            ReaderReadError{ file, err }  => write!(f, "Failed to read from input '{}': {}", file, err),
+            UnterminatedStringError{ .. } => write!(f, "Unterminated string literal (missing closing '\"')"),
+            ScanError{ err }              => write!(f, "Syntax error: {}", err),
+            Utf8Error{ file, offset }     => write!(f, "Input '{}' is not valid UTF-8 (invalid byte at offset {})", file, offset),
         }
     }
 }
@@ -147,8 +397,32 @@ impl PrettyError for ScanError {
     fn prettyprint_plain(&self, f: &mut Formatter<'_>) -> FResult {
         use self::ScanError::*;
         match self {
-            ReaderReadError{ .. } => error!(f, "{}", self),
-            ScanError{ .. }       => error!(f, "{}", self),
+            ReaderReadError{ .. } => error!(f, self.code(), "{}", self),
+            ScanError{ .. }       => error!(f, self.code(), "{}", self),
+            Utf8Error{ .. }       => error!(f, self.code(), "{}", self),
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
+        }
+    }
+
+    fn prettyprint_source(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::ScanError::*;
+        match self {
+            UnterminatedStringError{ source } => {
+                // Print the header with the message
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+                // Write the source reference
+                write!(f, "{}", source.display(Style::new().bold().red()))?;
+                writeln!(f)?;
+
+                // Done
+                Ok(())
+            },
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
         }
     }
 }
@@ -156,23 +430,103 @@ impl PrettyError for ScanError {
 
 
 /// Defines errors that may occur during parsing.
+/// 
+/// Every error has a stable code (see `ParseError::code()`), which is also shown when it is pretty-printed:
+/// 
+/// | Code    | Variant                  |
+/// |---------|--------------------------|
+/// | `E0001` | `EofError`               |
+/// | `E0002` | `UnexpectedTokenError`   |
+/// | `E0003` | `NonEmptyTokenList`      |
+/// | `E0004` | `UIntParseError`         |
+/// | `E0005` | `SIntParseError`         |
+/// | `E0006` | `BoolParseError`         |
+/// | `E0007` | `PortParseError`         |
+/// | `E0008` | `PortRangeError`         |
+/// | `E0009` | `StatusCodeParseError`   |
+/// | `E0010` | `UnknownActionError`     |
+/// | `E0011` | `NestingDepthError`      |
+/// | `E0012` | `DuplicateSettingError`  |
+/// | `E0013` | `NomError`               |
+/// | `E0014` | `MultipleErrors`         |
+/// | `E0015` | `IllegalPortError`       |
+/// | `E0016` | `DuplicateSectionError`  |
+/// | `E0017` | `SectionOrderError`      |
+/// | `E0018` | `IllegalStatusCodeError` |
+/// | `E0019` | `IllegalPatternError`    |
 #[derive(Debug)]
 pub enum ParseError {
     /// Failed to read the given reader as source text.
     NonEmptyTokenList{ remain: Vec<Token<SourceText>> },
     /// Failed to get the a token (got EOF instead).
-    EofError{ expected: Token<SourceText> },
+    EofError{ expected: Box<Token<SourceText>> },
     /// Failed to get a token (got another one instead).
-    UnexpectedTokenError{ got: Token<SourceText>, expected: Token<SourceText> },
+    UnexpectedTokenError{ got: Box<Token<SourceText>>, expected: Box<Token<SourceText>> },
 
     /// Failed to parse an unsigned integer
-    UIntParseError{ raw: String, err: std::num::ParseIntError, source: Option<SourceText> },
+    UIntParseError{ raw: String, err: std::num::ParseIntError, source: Option<Box<SourceText>> },
     /// Failed to parse a signed integer
-    SIntParseError{ raw: String, err: std::num::ParseIntError, source: Option<SourceText> },
+    SIntParseError{ raw: String, err: std::num::ParseIntError, source: Option<Box<SourceText>> },
     /// Failed to parse a boolean
-    BoolParseError{ raw: String, source: Option<SourceText> },
+    BoolParseError{ raw: String, source: Option<Box<SourceText>> },
+    /// Failed to parse a port number
+    PortParseError{ raw: String, err: std::num::ParseIntError, source: Option<Box<SourceText>> },
+    /// The given port number is not in the range of valid ports (1-65535)
+    IllegalPortError{ raw: String, source: Option<Box<SourceText>> },
+    /// The given port range ends before it starts
+    PortRangeError{ start: u16, end: u16, source: Option<Box<SourceText>> },
+    /// Failed to parse a status code
+    StatusCodeParseError{ raw: String, err: std::num::ParseIntError, source: Option<Box<SourceText>> },
+    /// The given status code is not an HTTP status code (100-599)
+    IllegalStatusCodeError{ status: u16, source: Option<Box<SourceText>> },
+    /// The given text is not a valid part of a pattern (e.g., a protocol or a path)
+    IllegalPatternError{ part: &'static str, raw: String, source: Option<Box<SourceText>> },
+    /// The given action is not known to us
+    UnknownActionError{ raw: String, source: Option<Box<SourceText>> },
+    /// A setting's value is nested too deeply
+    NestingDepthError{ max: usize, source: Option<Box<SourceText>> },
+    /// The same key is defined twice in the same settings block or dictionary
+    DuplicateSettingError{ key: String, range: TextRange, prev: TextRange },
+    /// The same section (`[settings]` or `[rules]`) occurs more than once
+    DuplicateSectionError{ section: &'static str, range: TextRange, prev: TextRange },
+    /// A `[settings]` section occurs after the `[rules]` section
+    SectionOrderError{ range: TextRange, rules: TextRange },
     /// Failed to parse (nom error)
     NomError{ errs: Vec<(nom::error::ErrorKind, Option<SourceText>)> },
+
+    /// Multiple errors occurred while parsing.
+    MultipleErrors{ errs: Vec<Self> },
+}
+
+impl ParseError {
+    /// Returns the stable, machine-readable code of this error.
+    /// 
+    /// # Returns
+    /// A code of the shape `E00xx`. Codes are never reused for other errors.
+    pub fn code(&self) -> &'static str {
+        use self::ParseError::*;
+        match self {
+            EofError{ .. }               => "E0001",
+            UnexpectedTokenError{ .. }   => "E0002",
+            NonEmptyTokenList{ .. }      => "E0003",
+            UIntParseError{ .. }         => "E0004",
+            SIntParseError{ .. }         => "E0005",
+            BoolParseError{ .. }         => "E0006",
+            PortParseError{ .. }         => "E0007",
+            PortRangeError{ .. }         => "E0008",
+            StatusCodeParseError{ .. }   => "E0009",
+            UnknownActionError{ .. }     => "E0010",
+            NestingDepthError{ .. }      => "E0011",
+            DuplicateSettingError{ .. }  => "E0012",
+            NomError{ .. }               => "E0013",
+            MultipleErrors{ .. }         => "E0014",
+            IllegalPortError{ .. }       => "E0015",
+            DuplicateSectionError{ .. }  => "E0016",
+            SectionOrderError{ .. }      => "E0017",
+            IllegalStatusCodeError{ .. } => "E0018",
+            IllegalPatternError{ .. }    => "E0019",
+        }
+    }
 }
 
 impl Display for ParseError {
@@ -183,10 +537,23 @@ impl Display for ParseError {
             EofError{ expected }                  => write!(f, "Syntax error: expected {}, got EOF", expected),
             UnexpectedTokenError{ got, expected } => write!(f, "Syntax error: expected {}, got {}", got, expected),
 
-            UIntParseError{ raw, err, .. } => write!(f, "Failed to parse '{}' as an unsigned integer: {}", raw, err),
-            SIntParseError{ raw, err, .. } => write!(f, "Failed to parse '{}' as a signed integer: {}", raw, err),
-            BoolParseError{ raw, .. }      => write!(f, "Failed to parse '{}' as a boolean", raw),
-            NomError{ errs, .. }           => write!(f, "Syntax error: {}", errs.iter().map(|(e, _)| format!("{:?}", e)).collect::<Vec<String>>().join(", ")),
+            UIntParseError{ raw, err, .. }       => write!(f, "Failed to parse '{}' as an unsigned integer: {}", raw, err),
+            SIntParseError{ raw, err, .. }       => write!(f, "Failed to parse '{}' as a signed integer: {}", raw, err),
+            BoolParseError{ raw, .. }            => write!(f, "Failed to parse '{}' as a boolean", raw),
+            PortParseError{ raw, err, .. }       => write!(f, "Failed to parse '{}' as a port number: {}", raw, err),
+            IllegalPortError{ raw, .. }          => write!(f, "Port number {} is out-of-range (expected a number in the range 1-65535)", raw),
+            PortRangeError{ start, end, .. }     => write!(f, "Port range {}-{} is empty (its start is larger than its end)", start, end),
+            StatusCodeParseError{ raw, err, .. } => write!(f, "Failed to parse '{}' as an HTTP status code: {}", raw, err),
+            IllegalStatusCodeError{ status, .. } => write!(f, "Status code {} is not an HTTP status code (expected a number in the range 100-599)", status),
+            IllegalPatternError{ part, raw, .. } => write!(f, "'{}' is not a valid {}", raw, part),
+            UnknownActionError{ raw, .. }        => write!(f, "Unknown action '!{}' (expected '!accept' or '!drop')", raw),
+            NestingDepthError{ max, .. }         => write!(f, "Lists and dictionaries may not be nested more than {} levels deep", max),
+            DuplicateSettingError{ key, .. }     => write!(f, "Setting '{}' is defined multiple times in the same block", key),
+            DuplicateSectionError{ section, .. } => write!(f, "Section '[{}]' occurs multiple times (merge them into one)", section),
+            SectionOrderError{ .. }              => write!(f, "Section '[settings]' must come before '[rules]'"),
+            NomError{ errs, .. }                 => write!(f, "Syntax error: {}", errs.iter().map(|(e, _)| format!("{:?}", e)).collect::<Vec<String>>().join(", ")),
+
+            MultipleErrors{ errs } => write!(f, "{} errors occurred while parsing", errs.len()),
         }
     }
 }
@@ -206,7 +573,8 @@ impl<'a> nom::error::ParseError<TokenList<'a>> for ParseError {
             // Done, store
             Self::NomError{ errs }
         } else {
-            panic!("Cannot append non-NomError to ParseError");
+            // Keep the original error as-is, since that's more informative than the nom context
+            other
         }
     }
 }
@@ -228,11 +596,60 @@ impl PrettyError for ParseError {
             EofError{ .. }             |
             UnexpectedTokenError{ .. } => {
                 // Print the header with the message, that's all
-                writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold())?;
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
                 writeln!(f)?;
                 Ok(())
             },
 
+            DuplicateSettingError{ range, prev, .. } => {
+                // Print the header with the message
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+                // Write the positions of both definitions, if any
+                if let TextRange::Some(start, _) = range {
+                    writeln!(f, " {} line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                if let TextRange::Some(start, _) = prev {
+                    writeln!(f, " {} previously defined at line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                writeln!(f)?;
+
+                // Done
+                Ok(())
+            },
+            DuplicateSectionError{ range, prev, .. } => {
+                // Print the header with the message
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+                // Write the positions of both headers, if any
+                if let TextRange::Some(start, _) = range {
+                    writeln!(f, " {} line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                if let TextRange::Some(start, _) = prev {
+                    writeln!(f, " {} first occurs at line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                writeln!(f)?;
+
+                // Done
+                Ok(())
+            },
+            SectionOrderError{ range, rules } => {
+                // Print the header with the message
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+                // Write the positions of both headers, if any
+                if let TextRange::Some(start, _) = range {
+                    writeln!(f, " {} line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                if let TextRange::Some(start, _) = rules {
+                    writeln!(f, " {} '[rules]' starts at line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+                }
+                writeln!(f)?;
+
+                // Done
+                Ok(())
+            },
+
             // Ignore the rest (for other functions)
             _ => Ok(()),
         }
@@ -243,9 +660,17 @@ impl PrettyError for ParseError {
         match self {
             UIntParseError{ source, .. } |
             SIntParseError{ source, .. } |
-            BoolParseError{ source, .. } => {
+            BoolParseError{ source, .. } |
+            PortParseError{ source, .. }         |
+            IllegalPortError{ source, .. }       |
+            PortRangeError{ source, .. }         |
+            StatusCodeParseError{ source, .. }   |
+            IllegalStatusCodeError{ source, .. } |
+            IllegalPatternError{ source, .. }    |
+            UnknownActionError{ source, .. }     |
+            NestingDepthError{ source, .. }      => {
                 // Print the header with the message
-                writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold())?;
+                writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
 
                 // Write the source reference, if any
                 if let Some(source) = source {
@@ -268,7 +693,7 @@ impl PrettyError for ParseError {
             NomError{ errs, .. } => {
                 for (_, source) in errs {
                     // Print the header with the message
-                    writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold())?;
+                    writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
 
                     // Write the source reference, if any
                     if let Some(source) = source {
@@ -280,6 +705,322 @@ impl PrettyError for ParseError {
                 // Done
                 Ok(())
             },
+            MultipleErrors{ errs } => {
+                for err in errs {
+                    err.prettyprint_fmt(f)?;
+                }
+                Ok(())
+            },
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
+        }
+    }
+}
+
+
+
+/// Defines errors that may occur during compiling.
+/// 
+/// Every error has a stable code (see `CompileError::code()`), which is also shown when it is pretty-printed:
+/// 
+/// | Code    | Variant                     |
+/// |---------|-----------------------------|
+/// | `E0201` | `UnknownSettingError`       |
+/// | `E0202` | `SettingTypeError`          |
+/// | `E0203` | `IllegalAddressError`       |
+/// | `E0204` | `IllegalPortError`          |
+/// | `E0205` | `CidrParseError`            |
+/// | `E0206` | `TlsPortError`              |
+/// | `E0207` | `UnloweredRuleError`        |
+/// | `E0208` | `UnsetVariableError`        |
+/// | `E0209` | `IllegalVariableError`      |
+/// | `E0210` | `NoPortsError`              |
+/// | `E0211` | `UnnormalizedHostnameError` |
+/// | `E0212` | `IllegalTargetError`        |
+/// | `E0213` | `MultipleErrors`            |
+#[derive(Debug)]
+pub enum CompileError {
+    /// The given setting is not known to us.
+    UnknownSettingError{ key: String, range: TextRange },
+    /// The given setting has a value of the wrong type.
+    SettingTypeError{ key: String, expected: &'static str, got: &'static str, range: TextRange },
+    /// Failed to parse the given address as an IP address.
+    IllegalAddressError{ raw: String, err: std::net::AddrParseError, range: TextRange },
+    /// The given port number is not a valid port.
+    IllegalPortError{ raw: u64, range: TextRange },
+    /// Failed to parse the given string as a CIDR block.
+    CidrParseError{ raw: String, err: ipnet::AddrParseError, range: TextRange },
+    /// The given TLS port is not one of the ports the proxy listens on.
+    TlsPortError{ port: u16, range: TextRange },
+    /// The given rule cannot be lowered to a hostname mapping.
+    UnloweredRuleError{ reason: String, range: TextRange },
+    /// A string refers to an environment variable that is not set (and has no default).
+    UnsetVariableError{ name: String, range: TextRange },
+    /// A string contains a reference to an environment variable that is not closed or has no name.
+    IllegalVariableError{ raw: String, range: TextRange },
+
+    /// The proxy does not listen on any ports.
+    NoPortsError,
+    /// A hostname in the Config is not normalized, so incoming requests can never match it.
+    UnnormalizedHostnameError{ hostname: String, range: TextRange },
+    /// The given target is not a valid `host` or `host:port`.
+    IllegalTargetError{ hostname: String, target: String, range: TextRange },
+
+    /// Multiple errors occurred while compiling.
+    MultipleErrors{ errs: Vec<Self> },
+}
+
+impl CompileError {
+    /// Returns the stable, machine-readable code of this error.
+    /// 
+    /// # Returns
+    /// A code of the shape `E02xx`. Codes are never reused for other errors.
+    pub fn code(&self) -> &'static str {
+        use self::CompileError::*;
+        match self {
+            UnknownSettingError{ .. }       => "E0201",
+            SettingTypeError{ .. }          => "E0202",
+            IllegalAddressError{ .. }       => "E0203",
+            IllegalPortError{ .. }          => "E0204",
+            CidrParseError{ .. }            => "E0205",
+            TlsPortError{ .. }              => "E0206",
+            UnloweredRuleError{ .. }        => "E0207",
+            UnsetVariableError{ .. }        => "E0208",
+            IllegalVariableError{ .. }      => "E0209",
+            NoPortsError                    => "E0210",
+            UnnormalizedHostnameError{ .. } => "E0211",
+            IllegalTargetError{ .. }        => "E0212",
+            MultipleErrors{ .. }            => "E0213",
+        }
+    }
+
+    /// Returns the range in the source text where this error occurred.
+    /// 
+    /// # Returns
+    /// The TextRange of the offending node, or `TextRange::None` if it has no origin in the source text.
+    pub fn range(&self) -> TextRange {
+        use self::CompileError::*;
+        match self {
+            UnknownSettingError{ range, .. }  => *range,
+            SettingTypeError{ range, .. }     => *range,
+            IllegalAddressError{ range, .. }  => *range,
+            IllegalPortError{ range, .. }     => *range,
+            CidrParseError{ range, .. }       => *range,
+            TlsPortError{ range, .. }         => *range,
+            UnloweredRuleError{ range, .. }   => *range,
+            UnsetVariableError{ range, .. }   => *range,
+            IllegalVariableError{ range, .. } => *range,
+
+            UnnormalizedHostnameError{ range, .. } => *range,
+            IllegalTargetError{ range, .. }        => *range,
+
+            NoPortsError         |
+            MultipleErrors{ .. } => TextRange::None,
+        }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::CompileError::*;
+        match self {
+            UnknownSettingError{ key, .. }             => write!(f, "Unknown setting '{}'", key),
+            SettingTypeError{ key, expected, got, .. } => write!(f, "Setting '{}' should be {}, but got {}", key, expected, got),
+            IllegalAddressError{ raw, err, .. }        => write!(f, "Failed to parse '{}' as an IP address: {}", raw, err),
+            IllegalPortError{ raw, .. }                => write!(f, "Port number {} is out-of-range (expected a number in the range 1-65535)", raw),
+            CidrParseError{ raw, err, .. }             => write!(f, "Failed to parse '{}' as a CIDR block: {}", raw, err),
+            TlsPortError{ port, .. }                   => write!(f, "TLS port {} is not one of the ports the proxy listens on (add it to 'ports')", port),
+            UnloweredRuleError{ reason, .. }           => write!(f, "Cannot use rule as a hostname mapping: {}", reason),
+            UnsetVariableError{ name, .. }             => write!(f, "Environment variable '{}' is not set (use '${{{}:-default}}' to give it a default value)", name, name),
+            IllegalVariableError{ raw, .. }            => write!(f, "String '{}' contains an unterminated or unnamed variable reference (expected '${{NAME}}' or '${{NAME:-default}}')", raw),
+
+            NoPortsError                               => write!(f, "The proxy does not listen on any ports (set 'ports')"),
+            UnnormalizedHostnameError{ hostname, .. }  => write!(f, "Hostname '{}' is not normalized, so it never matches (expected '{}')", hostname, crate::compiler::normalize_hostname(hostname)),
+            IllegalTargetError{ hostname, target, .. } => write!(f, "Hostname '{}' is proxied to '{}', which is not a valid 'host' or 'host:port'", hostname, target),
+
+            MultipleErrors{ errs } => write!(f, "{} errors occurred while compiling", errs.len()),
+        }
+    }
+}
+
+impl Error for CompileError {}
+
+impl PrettyError for CompileError {
+    fn prettyprint_plain(&self, f: &mut Formatter<'_>) -> FResult {
+        // Leave multiple errors to their own function
+        if let CompileError::MultipleErrors{ .. } = self { return Ok(()); }
+
+        // Print the header with the message
+        writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+        // Write the position, if any
+        if let TextRange::Some(start, _) = self.range() {
+            writeln!(f, " {} line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+        }
+        writeln!(f)?;
+
+        // Done
+        Ok(())
+    }
+
+    fn prettyprint_multiple(&self, f: &mut Formatter<'_>) -> FResult {
+        if let CompileError::MultipleErrors{ errs } = self {
+            for err in errs {
+                err.prettyprint_fmt(f)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn prettyprint_fmt_with(&self, source: &SourceRef, f: &mut Formatter<'_>) -> FResult {
+        // Multiple errors are all shown with the source
+        if let CompileError::MultipleErrors{ errs } = self {
+            for err in errs {
+                err.prettyprint_fmt_with(source, f)?;
+            }
+            return Ok(());
+        }
+
+        // Find the offending area in the source text, falling back to the position only if it's not there
+        let range: SourceRef = match SourceRef::from_range(source.name(), source.source(), self.range()) {
+            Some(range) => range,
+            None        => { return self.prettyprint_fmt(f); },
+        };
+
+        // Print the header with the message
+        writeln!(f, "{}{}", style(format!("error[{}]", self.code())).bold().red(), style(format!(": {}", self)).bold())?;
+
+        // Write the source reference
+        write!(f, "{}", range.display(Style::new().bold().red()))?;
+        writeln!(f)?;
+
+        // Done
+        Ok(())
+    }
+}
+
+
+
+/// Defines errors that may occur while formatting source text.
+#[derive(Debug)]
+pub enum FormatError {
+    /// Failed to read the file to format.
+    FileReadError{ path: PathBuf, err: std::io::Error },
+    /// Failed to write the formatted file back.
+    FileWriteError{ path: PathBuf, err: std::io::Error },
+
+    /// Failed to scan the source text.
+    ScanError{ err: ScanError },
+    /// Failed to parse the source text.
+    ParseError{ err: ParseError },
+    /// The source text contains a comment that is not kept in the AST, so it would be lost by formatting it.
+    CommentError{ source: SourceText },
+}
+
+impl Display for FormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::FormatError::*;
+        match self {
+            FileReadError{ path, err }  => write!(f, "Failed to read file '{}': {}", path.display(), err),
+            FileWriteError{ path, err } => write!(f, "Failed to write file '{}': {}", path.display(), err),
+
+            ScanError{ err }   => write!(f, "{}", err),
+            ParseError{ err }  => write!(f, "{}", err),
+            CommentError{ .. } => write!(f, "Cannot format source text with this comment, since it would be lost (only comments on their own line(s) above a section, setting or rule are kept)"),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+impl PrettyError for FormatError {
+    fn prettyprint_plain(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::FormatError::*;
+        match self {
+            FileReadError{ .. }  => writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold()),
+            FileWriteError{ .. } => writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold()),
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
+        }
+    }
+
+    fn prettyprint_source(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::FormatError::*;
+        match self {
+            ScanError{ err }       => err.prettyprint_fmt(f),
+            ParseError{ err }      => err.prettyprint_fmt(f),
+            CommentError{ source } => {
+                // Print the header with the message
+                writeln!(f, "{}{}", style("error").bold().red(), style(format!(": {}", self)).bold())?;
+
+                // Write the source reference
+                write!(f, "{}", source.display(Style::new().bold().red()))?;
+                writeln!(f)?;
+
+                // Done
+                Ok(())
+            },
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
+        }
+    }
+}
+
+
+
+/// Defines errors that may occur while loading source text in one go (i.e., scanning, parsing and compiling it).
+#[derive(Debug)]
+pub enum LoadError {
+    /// Failed to scan the source text.
+    ScanError{ err: ScanError },
+    /// Failed to parse the source text. If there were multiple errors, then this is a `ParseError::MultipleErrors`.
+    ParseError{ err: ParseError },
+    /// Failed to compile the parsed source text. The (whole) source text is kept to show where the error occurred.
+    CompileError{ err: CompileError, source: Box<SourceText> },
+    /// The compiled Config is not valid (see `compiler::Config::validate()`). The (whole) source text is kept to show where the errors occurred.
+    ValidateError{ errs: Vec<CompileError>, source: Box<SourceText> },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::LoadError::*;
+        match self {
+            ScanError{ err }      => write!(f, "{}", err),
+            ParseError{ err }     => write!(f, "{}", err),
+            CompileError{ err, .. }   => write!(f, "{}", err),
+            ValidateError{ errs, .. } => write!(f, "{} errors occurred while validating", errs.len()),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl PrettyError for LoadError {
+    fn prettyprint_source(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::LoadError::*;
+        match self {
+            ScanError{ err }            => err.prettyprint_fmt(f),
+            ParseError{ err }           => err.prettyprint_fmt(f),
+            CompileError{ err, source } => err.prettyprint_fmt_with(&SourceRef::new(source.name(), source.source()), f),
+
+            // Ignore the rest (for other functions)
+            _ => Ok(()),
+        }
+    }
+
+    fn prettyprint_multiple(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::LoadError::*;
+        match self {
+            ValidateError{ errs, source } => {
+                let source: SourceRef = SourceRef::new(source.name(), source.source());
+                for err in errs {
+                    err.prettyprint_fmt_with(&source, f)?;
+                }
+                Ok(())
+            },
 
             // Ignore the rest (for other functions)
             _ => Ok(()),
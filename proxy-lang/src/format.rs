@@ -0,0 +1,200 @@
+//  FORMAT.rs
+//    by Lut99
+// 
+//  Created:
+//    16 Oct 2026, 18:12:40
+//  Last edited:
+//    16 Oct 2026, 19:02:50
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements a formatter that rewrites source text of the proxy
+//!   language to its canonical form.
+// 
+
+use std::fs;
+use std::path::Path;
+
+pub use crate::errors::FormatError as Error;
+use crate::ast::Config;
+use crate::scanner::{self, Token};
+use crate::parser;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        // Messy-but-valid input should format to the canonical form...
+        let source: &str = "\n\n[settings]   ports:[80,443],\n  address :\"0.0.0.0\" ,\n\n\n\n[rules]\n\ttest.nl->example.com , www.test.nl  ->!accept,\nhttp://*.test.nl/api/*:8080   -> !drop 404 \"Not found\",\n\n";
+        let once: String = format_str("<test>", source).unwrap();
+        assert_eq!(once, "[settings]\nports: [ 80, 443 ],\naddress: \"0.0.0.0\",\n\n[rules]\ntest.nl -> example.com,\nwww.test.nl -> !accept,\nhttp://*.test.nl/api/*:8080 -> !drop 404 \"Not found\",\n");
+
+        // ...and formatting it again should yield exactly the same bytes
+        let twice: String = format_str("<test>", &once).unwrap();
+        assert_eq!(once.as_bytes(), twice.as_bytes());
+
+        // Empty input stays empty
+        assert_eq!(format_str("<test>", "  \n\n").unwrap(), "");
+    }
+
+    #[test]
+    fn test_format_comments() {
+        // Comments above areas, settings and rules and at the end are kept in place
+        let source: &str = "// The proxy's config\n\n[settings]\n  # Where to listen\n  ports:[80,443],\n\n[rules]\n/* The main site,\n   and its alias */\ntest.nl->example.com,\n// Block this one\n// for now\nwww.test.nl->!drop 404,\n\n// That's all\n";
+        let once: String = format_str("<test>", source).unwrap();
+        assert_eq!(once, "// The proxy's config\n[settings]\n# Where to listen\nports: [ 80, 443 ],\n\n[rules]\n/* The main site,\n   and its alias */\ntest.nl -> example.com,\n// Block this one\n// for now\nwww.test.nl -> !drop 404,\n\n// That's all\n");
+
+        // ...and formatting it again should yield exactly the same bytes
+        let twice: String = format_str("<test>", &once).unwrap();
+        assert_eq!(once.as_bytes(), twice.as_bytes());
+
+        // Files with only comments are fine too
+        let source: String = fs::read_to_string(format!("{}/comments.pconf", crate::tests::TEST_DIR)).unwrap();
+        let once: String = format_str("<test>", &source).unwrap();
+        assert_eq!(format_str("<test>", &once).unwrap(), once);
+    }
+
+    #[test]
+    fn test_format_errors() {
+        // Comments that cannot be kept are not thrown away silently
+        match format_str("<test>", "[rules]\ntest.nl -> /* Inside */ example.com,\n") {
+            Err(Error::CommentError{ source }) => { assert_eq!(source.as_str(), "/* Inside */"); },
+            res                                => { panic!("Expected a CommentError, got {:?}", res); },
+        }
+        assert!(matches!(format_str("<test>", "[rules]\ntest.nl -> example.com, /* trailing */\n"), Err(Error::CommentError{ .. })));
+
+        // Scan- and parse errors are propagated
+        assert!(matches!(format_str("<test>", "[rules]\ntest.nl -> \"example.com,\n"), Err(Error::ScanError{ .. })));
+        assert!(matches!(format_str("<test>", "[rules]\ntest.nl -> ,\n"), Err(Error::ParseError{ .. })));
+    }
+
+    #[test]
+    fn test_format_file() {
+        let path: std::path::PathBuf = env::temp_dir().join(format!("proxy-lang-test-format-{}.proxy", std::process::id()));
+
+        // A messy file is rewritten, after which it is left alone
+        fs::write(&path, "[rules]\ntest.nl->example.com,").unwrap();
+        assert!(format_file(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[rules]\ntest.nl -> example.com,\n");
+        assert!(!format_file(&path).unwrap());
+
+        // An invalid file is left untouched
+        fs::write(&path, "[rules]\ntest.nl ->,").unwrap();
+        assert!(matches!(format_file(&path), Err(Error::ParseError{ .. })));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[rules]\ntest.nl ->,");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Finds the first comment that was not attached to any node while parsing, and would thus be lost when writing the Config back.
+/// 
+/// # Arguments
+/// - `tokens`: The tokens (including comments) that the Config was parsed from.
+/// - `config`: The parsed Config.
+/// 
+/// # Returns
+/// The first comment token that is not kept in the Config, or `None` if they all are.
+fn find_dropped_comment<'a, 'b>(tokens: &'b [Token<'a>], config: &Config) -> Option<&'b Token<'a>> {
+    // Collect the kept comments in the order in which they appear
+    let mut kept: Vec<&str> = vec![];
+    for area in &config.config {
+        kept.extend(area.comments.iter().map(String::as_str));
+        for setting in &area.settings { kept.extend(setting.comments.iter().map(String::as_str)); }
+    }
+    for area in &config.patterns {
+        kept.extend(area.comments.iter().map(String::as_str));
+        for rule in &area.rules { kept.extend(rule.comments.iter().map(String::as_str)); }
+    }
+    kept.extend(config.comments.iter().map(String::as_str));
+
+    // Match them against the comments in the source text
+    let mut kept = kept.into_iter().peekable();
+    tokens.iter().filter(|t| matches!(t, Token::Comment(_, _))).find(|t| match t {
+        Token::Comment(text, _) if kept.peek() == Some(&text.as_str()) => { kept.next(); false },
+        _                                                              => true,
+    })
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Formats the given source text to its canonical form.
+/// 
+/// The canonical form is the one produced by the AST's `Display` implementation, i.e., one item per line and the areas separated by blank lines. Comments are kept above the area, setting or rule they precede.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to format.
+/// 
+/// # Returns
+/// The formatted source text.
+/// 
+/// # Errors
+/// This function errors if the source text failed to scan or parse, or if it contains comments that would be lost by formatting it (i.e., ones that are not on their own line(s) above an area, setting or rule, or at the end of the source text).
+pub fn format_str(name: &str, source: &str) -> Result<String, Error> {
+    // Scan the source text
+    let tokens: Vec<Token> = match scanner::scan_borrowed_with_comments(name, source) {
+        Ok(tokens) => tokens,
+        Err(err)   => { return Err(Error::ScanError{ err }); },
+    };
+
+    // Parse it
+    let config: Config = match parser::parse(&tokens) {
+        Ok(config) => config,
+        Err(err)   => { return Err(Error::ParseError{ err }); },
+    };
+
+    // Refuse to throw away comments that could not be attached to anything, then write it back
+    if let Some(Token::Comment(_, Some(source))) = find_dropped_comment(&tokens, &config) {
+        return Err(Error::CommentError{ source: source.into() });
+    }
+    Ok(config.to_string())
+}
+
+/// Formats the given file in-place.
+/// 
+/// The file is only written if formatting changed its contents, and never if it fails to format.
+/// 
+/// # Arguments
+/// - `path`: The path of the file to format.
+/// 
+/// # Returns
+/// Whether the file was changed.
+/// 
+/// # Errors
+/// This function errors if we failed to read or write the file, or if its contents failed to format (see `format_str()`).
+pub fn format_file(path: impl AsRef<Path>) -> Result<bool, Error> {
+    let path: &Path = path.as_ref();
+
+    // Read the file
+    let source: String = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(err)   => { return Err(Error::FileReadError{ path: path.into(), err }); },
+    };
+
+    // Format it
+    let name      : String = path.display().to_string();
+    let formatted : String = format_str(&name, &source)?;
+    if formatted == source { return Ok(false); }
+
+    // Write it back
+    if let Err(err) = fs::write(path, formatted) {
+        return Err(Error::FileWriteError{ path: path.into(), err });
+    }
+    Ok(true)
+}
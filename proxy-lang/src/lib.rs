@@ -4,7 +4,7 @@
 //  Created:
 //    07 Oct 2022, 21:48:58
 //  Last edited:
-//    22 Oct 2022, 14:50:02
+//    16 Oct 2026, 18:54:08
 //  Auto updated?
 //    Yes
 // 
@@ -13,15 +13,25 @@
 //!   compiler for the custom proxy language that is used to configure it.
 // 
 
+// Silence some lints that go against the structure of this crate
+#![allow(clippy::items_after_test_module)]
+#![allow(clippy::module_inception)]
+
 // Declare modules
 pub mod errors;
 pub mod warnings;
 pub mod spec;
 pub mod source;
 pub mod tokens;
-// pub mod ast;
+pub mod ast;
 pub mod scanner;
-// pub mod parser;
+pub mod parser;
+pub mod compiler;
+pub mod format;
+pub mod loader;
+
+// Pull the one-shot entrypoints into the crate namespace
+pub use loader::{compile_to_proxy_config, load};
 
 // Declare test modules
 #[cfg(test)]
@@ -0,0 +1,134 @@
+//  LOADER.rs
+//    by Lut99
+// 
+//  Created:
+//    16 Oct 2026, 18:43:01
+//  Last edited:
+//    16 Oct 2026, 19:11:37
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Implements one-shot functions that run the whole pipeline (i.e.,
+//!   scanning, parsing and compiling) on some source text.
+// 
+
+pub use crate::errors::LoadError as Error;
+use crate::ast;
+use crate::compiler::{self, Config};
+use crate::parser::{self, ParseResult};
+use crate::scanner::{self, Token};
+use crate::source::SourceRef;
+use crate::warnings::Warning;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::errors::{init_colors, CompileError, ParseError, PrettyError as _};
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        // Comments are allowed anywhere a scanner would skip them
+        let config: ast::Config = load("<test>", "// Ports\n[settings]\nports: [ 80 ],\n\n[rules]\ntest.nl -> example.com, # Main site\n").unwrap();
+        assert_eq!(config.config.len(), 1);
+        assert_eq!(config.patterns.len(), 1);
+
+        // Errors of every stage are returned as-is
+        assert!(matches!(load("<test>", "name: \"unterminated"), Err(Error::ScanError{ .. })));
+        assert!(matches!(load("<test>", "[rules]\ntest.nl ->,"), Err(Error::ParseError{ .. })));
+        assert!(matches!(load("<test>", "[rules]\na.nl ->,\nb.nl ->,"), Err(Error::ParseError{ err: ParseError::MultipleErrors{ ref errs } }) if errs.len() == 2));
+    }
+
+    #[test]
+    fn test_compile_to_proxy_config() {
+        // Compile a simple config
+        let (config, warnings): (Config, Vec<Warning>) = compile_to_proxy_config("<test>", "[settings]\nports: [ 80 ],\n\n[rules]\ntest.nl -> example.com,\n").unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(config.ports, vec![ 80 ]);
+        assert_eq!(config.hostnames.get("test.nl").map(|t| t.as_str()), Some("example.com"));
+
+        // Compile- and validation errors are returned, too
+        assert!(matches!(compile_to_proxy_config("<test>", "[settings]\nfoo: 42,\n"), Err(Error::CompileError{ err: CompileError::UnknownSettingError{ .. }, .. })));
+        assert!(matches!(compile_to_proxy_config("<test>", "[rules]\ntest.nl -> example.com,\n"), Err(Error::ValidateError{ ref errs, .. }) if matches!(errs.as_slice(), [ CompileError::NoPortsError ])));
+
+        // They are all shown with an excerpt of the source text
+        init_colors(true);
+        let text: String = format!("{}", compile_to_proxy_config("<test>", "[settings]\nfoo: 1,\nbar: 2,\n").unwrap_err().prettyprint());
+        assert_eq!(text.matches("error[E0201]").count(), 2);
+        assert!(text.contains(" --> <test>:2:1\n  |\n2 | foo: 1,\n"));
+        assert!(text.contains(" --> <test>:3:1\n  |\n3 | bar: 2,\n"));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Scans the given source text and parses as much of it as possible.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to parse.
+/// 
+/// # Returns
+/// The ParseResult with the (partial) AST and any errors.
+/// 
+/// # Errors
+/// This function errors if the source text failed to scan.
+fn load_partial(name: &str, source: &str) -> Result<ParseResult, Error> {
+    let tokens: Vec<Token> = match scanner::scan_borrowed(name, source) {
+        Ok(tokens) => tokens,
+        Err(err)   => { return Err(Error::ScanError{ err }); },
+    };
+    Ok(parser::parse_partial(&tokens))
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Scans and parses the given source text in one go.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to load.
+/// 
+/// # Returns
+/// The root node of the parsed AST.
+/// 
+/// # Errors
+/// This function errors if the source text failed to scan or parse. Parse errors are recovered from as much as possible, so all of them are returned at once (as a `ParseError::MultipleErrors` if there are several).
+pub fn load(name: &str, source: &str) -> Result<ast::Config, Error> {
+    match load_partial(name, source)?.into_result() {
+        Ok(config) => Ok(config),
+        Err(err)   => Err(Error::ParseError{ err }),
+    }
+}
+
+/// Scans, parses and compiles the given source text in one go, and then validates the result.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to compile.
+/// 
+/// # Returns
+/// A new Config that can be used by the proxy, together with a list of non-fatal warnings encountered while compiling it. Use `PrettyWarning::prettyprint_with()` to show those with the given `name` and `source`.
+/// 
+/// # Errors
+/// This function errors if the source text failed to scan, parse or compile (see `load()` and `compiler::compile()`), or if the compiled Config is not valid (see `Config::validate()`). Like scan- and parse errors, compile- and validation errors keep the source text, so they are pretty-printed with an excerpt of it.
+pub fn compile_to_proxy_config(name: &str, source: &str) -> Result<(Config, Vec<Warning>), Error> {
+    // Parse the source text, then compile it
+    let config: ast::Config = load(name, source)?;
+    let (config, warnings): (Config, Vec<Warning>) = match compiler::compile(&config) {
+        Ok(res)  => res,
+        Err(err) => { return Err(Error::CompileError{ err, source: Box::new(SourceRef::new(name, source).into()) }); },
+    };
+
+    // Make sure it's runnable
+    if let Err(errs) = config.validate() { return Err(Error::ValidateError{ errs, source: Box::new(SourceRef::new(name, source).into()) }); }
+    Ok((config, warnings))
+}
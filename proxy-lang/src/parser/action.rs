@@ -4,7 +4,7 @@
 //  Created:
 //    14 Oct 2022, 11:06:27
 //  Last edited:
-//    14 Oct 2022, 11:06:32
+//    16 Oct 2026, 18:54:08
 //  Auto updated?
 //    Yes
 // 
@@ -12,5 +12,178 @@
 //!   Contains code for parsing Actions off Token streams.
 // 
 
+use std::str::FromStr;
+
+use nom::IResult;
+use nom::{branch, combinator as comb, sequence as seq};
+
+pub use crate::errors::ParseError as Error;
+use crate::spec::{Node, TextRange};
+use crate::tokens::{Token, TokenList};
+use crate::ast::Action;
+use crate::parser::tag;
+use crate::parser::pattern;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::ast::{Endpoint, Port};
+    use crate::spec::TextPos;
+    use crate::tests::scan_tokens;
+    use super::*;
+
+    #[test]
+    fn test_action() {
+        // Accept
+        let tokens = scan_tokens("!accept");
+        let (rest, action): (TokenList, Action) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(action, Action::Accept(_)));
+        assert_eq!(action.range(), TextRange::new(TextPos::new(1, 1), TextPos::new(1, 7)));
+
+        // Rewrite
+        let tokens = scan_tokens("https://example.com:443 ");
+        let (rest, action): (TokenList, Action) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(action, Action::Rewrite(ref p) if matches!(p.base, Endpoint::Specific(ref b, _) if b == "example.com") && matches!(p.port, Port::Specific(443, _))));
+
+        // Drop with a message
+        let tokens = scan_tokens("!drop 404 \"Not found\"");
+        let (rest, action): (TokenList, Action) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(action, Action::Drop(404, Some(ref msg), _) if msg == "Not found"));
+        let tokens = scan_tokens("!drop 403 \"Access \\\"denied\\\"\\n\"");
+        let (rest, action): (TokenList, Action) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(action, Action::Drop(403, Some(ref msg), _) if msg == "Access \"denied\"\n"));
+        assert_eq!(action.to_string(), "!drop 403 \"Access \\\"denied\\\"\\n\"");
+
+        // Drop without a message
+        let tokens = scan_tokens("!drop 500");
+        let (rest, action): (TokenList, Action) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(action, Action::Drop(500, None, _)));
+        assert_eq!(action.range(), TextRange::new(TextPos::new(1, 1), TextPos::new(1, 9)));
+    }
+
+    #[test]
+    fn test_action_errors() {
+        // Unknown actions are hard errors
+        let tokens = scan_tokens("!forward");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::UnknownActionError{ .. }))));
+
+        // So are missing or invalid status codes
+        let tokens = scan_tokens("!drop");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(_))));
+        let tokens = scan_tokens("!drop 99999");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::StatusCodeParseError{ .. }))));
+        let tokens = scan_tokens("!drop 600");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalStatusCodeError{ status: 600, .. }))));
+        let tokens = scan_tokens("!drop 99 \"Too low\"");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalStatusCodeError{ status: 99, .. }))));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the status code and optional message of a drop action.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed status code and message, together with the range of the last token parsed.
+/// 
+/// # Errors
+/// This function returns a failure if the status code is missing or not a valid HTTP status code (i.e., in `100..=599`).
+fn parse_drop<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, (u16, Option<String>, TextRange), Error> {
+    let (rest, (code, message)): (TokenList<'a>, (TokenList<'a>, Option<TokenList<'a>>)) = seq::pair(
+        comb::cut(branch::alt((
+            tag!(Token::Port, String::new()),
+            tag!(Token::UInt, String::new()),
+            tag!(Token::Identifier, String::new()),
+        ))),
+        comb::opt(tag!(Token::String, String::new())),
+    )(input)?;
+
+    // Parse the status code
+    let status: u16 = match &code[0] {
+        Token::Port(raw, source)       |
+        Token::UInt(raw, source)       |
+        Token::Identifier(raw, source) => match u16::from_str(raw) {
+            Ok(status) if (100..=599).contains(&status) => status,
+            Ok(status)                                  => { return Err(nom::Err::Failure(Error::IllegalStatusCodeError{ status, source: source.map(|s| Box::new(s.into())) })); },
+            Err(err)                                    => { return Err(nom::Err::Failure(Error::StatusCodeParseError{ raw: raw.clone(), err, source: source.map(|s| Box::new(s.into())) })); },
+        },
+
+        _ => { panic!("Got a non-Port, non-UInt, non-Identifier token when those are the only possibility"); },
+    };
+
+    // Extract the message, if any
+    match message {
+        Some(message) => if let Token::String(value, _) = &message[0] {
+            Ok((rest, (status, Some(value.clone()), message[0].range())))
+        } else {
+            panic!("Got a non-String token when a String is the only possibility");
+        },
+        None => Ok((rest, (status, None, code[0].range()))),
+    }
+}
+
+/// Parses a special action (i.e., one prefixed with an exclamation mark, like `!accept` or `!drop`).
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed Action.
+/// 
+/// # Errors
+/// This function returns an error if the top token is not an action, or a failure if the action is unknown or malformed.
+fn parse_special<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Action, Error> {
+    let (rest, action): (TokenList<'a>, TokenList<'a>) = tag!(Token::Action, String::new())(input)?;
+    let (name, source) = if let Token::Action(name, source) = &action[0] {
+        (name, source)
+    } else {
+        panic!("Got a non-Action token when an Action is the only possibility");
+    };
+
+    // Match on the action's name
+    match name.to_lowercase().as_str() {
+        "accept" => Ok((rest, Action::Accept(action[0].range()))),
+        "drop"   => {
+            let (rest, (status, message, end)): (TokenList<'a>, (u16, Option<String>, TextRange)) = parse_drop(rest)?;
+            Ok((rest, Action::Drop(status, message, TextRange::new(action[0].start(), end.end()))))
+        },
+
+        _ => Err(nom::Err::Failure(Error::UnknownActionError{ raw: name.clone(), source: source.map(|s| Box::new(s.into())) })),
+    }
+}
+
+
+
+
 
 /***** LIBRARY *****/
+/// Parses an action off the given list of tokens.
+/// 
+/// An action is either `!accept`, `!drop <status> ["<message>"]` or a pattern to rewrite the incoming request to.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// An Action if we were able to parse one.
+/// 
+/// # Errors
+/// This function returns an error if we failed to parse an action.
+pub fn parse<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Action, Error> {
+    branch::alt((
+        parse_special,
+        comb::map(pattern::parse, |pattern| Action::Rewrite(Box::new(pattern))),
+    ))(input)
+}
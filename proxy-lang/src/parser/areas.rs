@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2022, 23:32:03
 //  Last edited:
-//    14 Oct 2022, 11:01:06
+//    16 Oct 2026, 19:08:45
 //  Auto updated?
 //    Yes
 // 
@@ -13,67 +13,141 @@
 // 
 
 
-use nom::IResult;
-use nom::{combinator as comb, multi, sequence as seq};
+use nom::{IResult, InputTake as _};
 
 pub use crate::errors::ParseError as Error;
-use crate::spec::{Node, TextRange};
-use crate::tokens::Token;
+use crate::spec::{Node, TextPos, TextRange};
+use crate::source::{SourceRef, SourceText};
+use crate::tokens::{Token, TokenList};
 use crate::ast::{Rule, RulesArea, Setting, SettingsArea};
-use crate::parser::tag;
 use crate::parser::settings;
 use crate::parser::rule;
 
 
-/***** LIBRARY *****/
-/// Parses a settings area off the list of tokens.
+/***** HELPER FUNCTIONS *****/
+/// Returns whether the given token is a section header (i.e., `[settings]` or `[rules]`).
+#[inline]
+pub(crate) fn is_section(token: &Token<SourceRef>) -> bool { matches!(token, Token::SettingsSection(_) | Token::RulesSection(_)) }
+
+/// Skips tokens until we are at a likely synchronization point, which is either just after the next comma or just before the next section header.
 /// 
 /// # Arguments
-/// - `input`: The list of tokens.
+/// - `input`: The list of tokens to skip in.
 /// 
 /// # Returns
-/// A SettingsArea if we were able to parse one.
-/// 
-/// # Errors
-/// This function returns an error if we failed to parse the area.
-pub fn parse_settings<'a>(input: &'a [Token]) -> IResult<&'a [Token], SettingsArea, Error> {
-    comb::map(
-        seq::tuple((
-            tag!(Token::SettingsSection),
-            multi::many0(settings::parse),
-        )),
-        |(header, settings): (&'a [Token], Vec<Setting>)| {
-            let range: TextRange = TextRange::new(header[0].start(), if !settings.is_empty() { settings[settings.len() - 1].end() } else { header[0].end() });
-            SettingsArea {
-                settings,
-                range,
-            }
-        },
-    )(input)
+/// The remaining tokens after skipping.
+fn synchronize(input: TokenList) -> TokenList {
+    for (i, token) in input.iter().enumerate() {
+        match token {
+            Token::Comma(_)            => { return input.take_split(i + 1).0; },
+            token if is_section(token) => { return input.take_split(i).0; },
+            _                          => {},
+        }
+    }
+    input.take_split(input.len()).0
 }
 
-/// Parses a rule area off the list of tokens.
+/// Collapses the given error for a single item into one diagnostic that does not run past the tokens skipped by `synchronize()`.
+/// 
+/// Syntax errors from nom have an entry for every parser that backtracked, which all describe the same problem and span the rest of the input. We only keep the first (innermost) entry, and let it run from where it occurred up to the last skipped token.
 /// 
 /// # Arguments
-/// - `input`: The list of tokens.
+/// - `err`: The error to collapse.
+/// - `input`: The list of tokens that the item was parsed from.
+/// - `skipped`: The number of tokens that `synchronize()` skipped.
 /// 
 /// # Returns
-/// A RulesArea if we were able to parse one.
+/// The collapsed error, or `err` as-is if it is not a syntax error from nom.
+fn collapse(err: Error, input: TokenList, skipped: usize) -> Error {
+    let (kind, source): (nom::error::ErrorKind, Option<SourceText>) = match err {
+        Error::NomError{ mut errs } if !errs.is_empty() => errs.swap_remove(0),
+        err                                            => { return err; },
+    };
+
+    // Find the token where the error occurred (which is the last one if it occurred at the end of the input)
+    let start : Option<TextPos> = source.and_then(|source| source.range().start());
+    let first : usize           = start.and_then(|start| input.iter().position(|token| token.start().map(|s| s >= start).unwrap_or(false))).unwrap_or(input.len() - 1);
+    let last  : usize           = first.max(skipped - 1);
+
+    // Build the error from there
+    let source: Option<SourceText> = match (input[first].source(), input[last].source()) {
+        (Some(lhs), Some(rhs)) => Some((lhs + rhs).into()),
+        _                      => None,
+    };
+    Error::NomError{ errs: vec![ (kind, source) ] }
+}
+
+/// Parses the body of an area, recovering from errors by skipping to the next synchronization point.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens to parse from (just after the section header).
+/// - `parser`: The parser for a single item in the area (e.g., `rule::parse`).
+/// - `errors`: A list of errors to which we add any errors we encounter.
 /// 
-/// # Errors
-/// This function returns an error if we failed to parse the area.
-pub fn parse_rules<'a>(input: &'a [Token]) -> IResult<&'a [Token], RulesArea, Error> {
-    comb::map(
-        seq::tuple((
-            tag!(Token::RulesSection),
-            multi::many0(rule::parse),
-        )),
-        |(header, rules): (&'a [Token], Vec<Rule>)| {
-            let range: TextRange = TextRange::new(header[0].start(), if !rules.is_empty() { rules[rules.len() - 1].end() } else { header[0].end() });
-            RulesArea {
-                rules,
-                range,
-            }
+/// # Returns
+/// The remaining tokens and the items that we successfully parsed.
+fn parse_body<'a, T>(input: TokenList<'a>, mut parser: impl FnMut(TokenList<'a>) -> IResult<TokenList<'a>, T, Error>, errors: &mut Vec<Error>) -> (TokenList<'a>, Vec<T>) {
+    let mut input : TokenList<'a> = input;
+    let mut items : Vec<T>        = vec![];
+    while !input.is_empty() && !is_section(&input[0]) {
+        match parser(input) {
+            Ok((rest, item)) => {
+                items.push(item);
+                input = rest;
+            },
+            Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+                let rest: TokenList<'a> = synchronize(input);
+                errors.push(collapse(err, input, input.len() - rest.len()));
+                input = rest;
+            },
+            Err(nom::Err::Incomplete(_)) => { panic!("Got `nom::Err::Incomplete` while parsing a complete list of tokens"); },
         }
-    )(input)
+    }
+    (input, items)
+}
+
+
+
+
+
+/***** LIBRARY *****/
+/// Parses a settings area, recovering from any errors in its settings.
+/// 
+/// Settings that are defined multiple times in the same area are reported as errors too.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens to parse from. Its first token is assumed to be the `[settings]` header.
+/// - `errors`: A list of errors to which we add any errors we encounter.
+/// 
+/// # Returns
+/// The remaining tokens and the parsed SettingsArea.
+pub fn parse_settings<'a>(input: TokenList<'a>, errors: &mut Vec<Error>) -> (TokenList<'a>, SettingsArea) {
+    let (rest, header)   : (TokenList<'a>, TokenList<'a>) = input.take_split(1);
+    let (rest, settings) : (TokenList<'a>, Vec<Setting>)  = parse_body(rest, settings::parse, errors);
+    errors.extend(settings::find_duplicates(&settings));
+    let range: TextRange = header[0].range().join(TextRange::spanning(settings.iter().map(Node::range)));
+    (rest, SettingsArea {
+        settings,
+        comments : vec![],
+        range,
+    })
+}
+
+/// Parses a rules area, recovering from any errors in its rules.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens to parse from. Its first token is assumed to be the `[rules]` header.
+/// - `errors`: A list of errors to which we add any errors we encounter.
+/// 
+/// # Returns
+/// The remaining tokens and the parsed RulesArea.
+pub fn parse_rules<'a>(input: TokenList<'a>, errors: &mut Vec<Error>) -> (TokenList<'a>, RulesArea) {
+    let (rest, header) : (TokenList<'a>, TokenList<'a>) = input.take_split(1);
+    let (rest, rules)  : (TokenList<'a>, Vec<Rule>)     = parse_body(rest, rule::parse, errors);
+    let range: TextRange = header[0].range().join(TextRange::spanning(rules.iter().map(Node::range)));
+    (rest, RulesArea {
+        rules,
+        comments : vec![],
+        range,
+    })
 }
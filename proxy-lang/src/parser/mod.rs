@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2022, 23:04:18
 //  Last edited:
-//    16 Oct 2022, 15:15:38
+//    16 Oct 2026, 18:54:08
 //  Auto updated?
 //    Yes
 // 
@@ -22,28 +22,65 @@ pub mod areas;
 pub mod parser;
 
 // Pull stuff into the global namespace
-pub use parser::{parse, Error};
+pub use parser::{parse, parse_partial, Error, ParseResult};
 
-// Useful macros
-macro_rules! tag {
-    (Token::$var:ident) => {
-        move |tokens: crate::tokens::TokenList| -> nom::IResult<crate::tokens::TokenList, crate::tokens::Token, crate::errors::ParseError> {
-            use nom::InputTake;
+// Pull in some imports for the helpers
+use nom::{IResult, InputTake as _};
+use nom::{combinator as comb, multi};
+
+use crate::errors::ParseError;
+use crate::source::SourceRef;
+use crate::tokens::{Token, TokenList};
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses a single token off the given list of tokens if it has the same variant as the given one. Its value is ignored.
+/// 
+/// # Arguments
+/// - `expected`: The token to match. Only its variant is compared.
+/// 
+/// # Returns
+/// A nom parser that returns the matched token as a TokenList of size 1.
+/// 
+/// # Errors
+/// The returned parser errors if there are no tokens left, or the next token is of another variant.
+pub(crate) fn token<'a>(expected: Token<SourceRef<'a>>) -> impl FnMut(TokenList<'a>) -> IResult<TokenList<'a>, TokenList<'a>, ParseError> {
+    move |tokens: TokenList<'a>| {
+        // Attempt to get the given token from the list
+        if tokens.is_empty() { return Err(nom::Err::Error(ParseError::EofError{ expected: Box::new(expected.clone().into()) })); }
+        let (res, token): (TokenList<'a>, TokenList<'a>) = tokens.take_split(1);
+
+        // Make sure if they are the same, then return (note that the `PartialEq` of Tokens only compares variants)
+        if token[0] != expected { return Err(nom::Err::Error(ParseError::UnexpectedTokenError{ got: Box::new(token[0].clone().into()), expected: Box::new(expected.clone().into()) })); }
+        Ok((res, token))
+    }
+}
+
+
+/// Skips any comment tokens at the start of the given list of tokens.
+/// 
+/// # Arguments
+/// - `tokens`: The list of tokens to skip comments in.
+/// 
+/// # Returns
+/// The remaining tokens after the comments.
+/// 
+/// # Errors
+/// This function never errors, but has to return an `IResult` to be usable as a nom parser.
+pub(crate) fn skip_comments(tokens: TokenList) -> IResult<TokenList, (), ParseError> {
+    comb::value((), multi::many0(tag!(Token::Comment, String::new())))(tokens)
+}
 
-            // Create a vanilla, to-be-comparsed token
-            let expected: crate::tokens::Token = crate::tokens::Token::$var(crate::spec::TextRange::None);
 
-            // Attempt to get the given token from the list
-            if tokens.is_empty() { return Err(nom::Err::Error(crate::errors::ParseError::EofError{ expected })); }
-            let (token, res): (crate::tokens::TokenList, crate::tokens::TokenList) = tokens.take_split(1);
 
-            // Make sure if they are the same, then return
-            if std::mem::discriminant(&token[0]) != std::mem::discriminant(&expected) { return Err(nom::Err::Error(crate::errors::ParseError::UnexpectedTokenError{ got: tokens[0].clone(), expected })); }
-            Ok((res, token[0]))
-        }
+// Useful macros
+/// Parses a single token of the given variant off the list of tokens.
+macro_rules! tag {
+    (Token::$var:ident) => {
+        crate::parser::token(crate::tokens::Token::$var(None))
     };
     (Token::$var:ident, $($val:expr),+) => {
-        nom::bytes::complete::tag(crate::tokens::Token::$var($($val),+, crate::spec::TextRange::None))
+        crate::parser::token(crate::tokens::Token::$var($($val),+, None))
     };
 }
 pub(crate) use tag;
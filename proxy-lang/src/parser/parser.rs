@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2022, 23:04:50
 //  Last edited:
-//    12 Oct 2022, 15:41:06
+//    16 Oct 2026, 19:08:45
 //  Auto updated?
 //    Yes
 // 
@@ -12,50 +12,352 @@
 //!   Implements the toplevel parsing functions.
 // 
 
-use nom::IResult;
-use nom::{branch, combinator as comb, multi};
+use nom::InputTake as _;
 
 pub use crate::errors::ParseError as Error;
-use crate::spec::{TextRange, TokenList};
-use crate::tokens::Token;
-use crate::ast::{Config, Pattern};
-use crate::parser::areas;
+use crate::spec::{Node, TextPos, TextRange};
+use crate::source::SourceRef;
+use crate::tokens::{Token, TokenList};
+use crate::ast::{Config, RulesArea, SettingsArea};
+use crate::parser::areas::{self, is_section};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::scanner::scan_borrowed_with_comments;
+    use crate::tests::scan_tokens;
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        // Parse a simple config
+        let tokens = scan_tokens("[settings]\nport: 80,\n\n[rules]\ntest.nl -> example.com,\nexample.com -> !accept,");
+        let config: Config = parse(&tokens).unwrap();
+        assert_eq!(config.config.len(), 1);
+        assert_eq!(config.config[0].settings.len(), 1);
+        assert_eq!(config.patterns.len(), 1);
+        assert_eq!(config.patterns[0].rules.len(), 2);
+
+        // The last setting in an area may omit its comma
+        let with: Config = parse(&scan_tokens("[settings]\nport: 80,\naddress: \"0.0.0.0\",\n[rules]\ntest.nl -> example.com,")).unwrap();
+        let without: Config = parse(&scan_tokens("[settings]\nport: 80,\naddress: \"0.0.0.0\"\n[rules]\ntest.nl -> example.com,")).unwrap();
+        assert_eq!(with, without);
+        assert_eq!(parse(&scan_tokens("[settings]\nport: 80")).unwrap().config[0].settings.len(), 1);
+        assert!(parse(&scan_tokens("[settings]\nport: 80\naddress: \"0.0.0.0\",")).is_err());
+    }
+
+    #[test]
+    fn test_parse_recovery() {
+        // Two independently broken rules should yield two errors
+        let tokens = scan_tokens("[rules]\ntest.nl -> ,\nexample.com -> test.nl,\nfoo.nl -> -> bar.nl,");
+        match parse(&tokens) {
+            Err(Error::MultipleErrors{ errs }) => {
+                assert_eq!(errs.len(), 2);
+                assert!(errs.iter().all(|e| matches!(e, Error::NomError{ .. } | Error::UnexpectedTokenError{ .. })));
+            },
+            res => { panic!("Expected two errors, got {:?}", res); },
+        }
+
+        // Each of them is reported once, and only spans up to the end of its own rule
+        match parse(&tokens) {
+            Err(Error::MultipleErrors{ errs }) => match &errs[0] {
+                Error::NomError{ errs } => {
+                    assert_eq!(errs.len(), 1);
+                    assert_eq!(errs[0].1.as_ref().map(|s| s.range()), Some(TextRange::new(TextPos::new(2, 12), TextPos::new(2, 12))));
+                },
+                err => { panic!("Expected a syntax error, got {:?}", err); },
+            },
+            res => { panic!("Expected two errors, got {:?}", res); },
+        }
+
+        // Same for settings, and we don't lose the section after it
+        let tokens = scan_tokens("[settings]\nport 80,\nfoo: [,\n[rules]\ntest.nl -> ,");
+        assert!(matches!(parse(&tokens), Err(Error::MultipleErrors{ errs }) if errs.len() == 3));
+
+        // Duplicate keys in the same area are errors, but not in different areas
+        let tokens = scan_tokens("[settings]\nport: 80,\nport: 80,");
+        match parse(&tokens) {
+            Err(Error::DuplicateSettingError{ key, range, prev }) => {
+                assert_eq!(key, "port");
+                assert_eq!(range, TextRange::new(TextPos::new(3, 1), TextPos::new(3, 4)));
+                assert_eq!(prev, TextRange::new(TextPos::new(2, 1), TextPos::new(2, 4)));
+            },
+            res => { panic!("Expected a duplicate setting error, got {:?}", res); },
+        }
+        let tokens = scan_tokens("[settings]\nport: 80,\n[rules]\ntest.nl -> example.com,");
+        assert!(parse(&tokens).is_ok());
+
+        // A single error is returned as-is
+        let tokens = scan_tokens("[rules]\ntest.nl -> ,");
+        assert!(!matches!(parse(&tokens), Ok(_) | Err(Error::MultipleErrors{ .. })));
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        // Empty, whitespace-only and comment-only inputs parse to an empty Config without a range
+        for source in [ "", "  \n\t\n", "// Nothing here\n/* or\nhere */\n# nor here" ] {
+            let config: Config = parse(&scan_borrowed_with_comments("<test>", source).unwrap()).unwrap();
+            assert!(config.config.is_empty());
+            assert!(config.patterns.is_empty());
+            assert_eq!(config.range, TextRange::None);
+            assert_eq!(crate::compiler::compile(&config).unwrap().0, crate::compiler::Config::default());
+        }
+
+        // Empty areas span just their header
+        let config: Config = parse(&scan_tokens("[settings]\n[rules]\n")).unwrap();
+        assert_eq!(config.config[0].range, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 10)));
+        assert_eq!(config.patterns[0].range, TextRange::new(TextPos::new(2, 1), TextPos::new(2, 7)));
+        assert_eq!(config.range, TextRange::new(TextPos::new(1, 1), TextPos::new(2, 7)));
+    }
+
+    #[test]
+    fn test_parse_sections() {
+        // Settings, then rules, is fine (and either may be omitted)
+        assert!(parse(&scan_tokens("[settings]\nport: 80,\n[rules]\ntest.nl -> example.com,")).is_ok());
+        assert!(parse(&scan_tokens("[settings]\nport: 80,")).is_ok());
+        assert!(parse(&scan_tokens("[rules]\ntest.nl -> example.com,")).is_ok());
+
+        // The other way around is not
+        match parse(&scan_tokens("[rules]\ntest.nl -> example.com,\n[settings]\nport: 80,")) {
+            Err(Error::SectionOrderError{ range, rules }) => {
+                assert_eq!(range, TextRange::new(TextPos::new(3, 1), TextPos::new(3, 10)));
+                assert_eq!(rules, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 7)));
+            },
+            res => { panic!("Expected a section order error, got {:?}", res); },
+        }
+
+        // Neither are duplicate sections
+        match parse(&scan_tokens("[settings]\nport: 80,\n[settings]\naddress: \"0.0.0.0\",")) {
+            Err(Error::DuplicateSectionError{ section, range, prev }) => {
+                assert_eq!(section, "settings");
+                assert_eq!(range, TextRange::new(TextPos::new(3, 1), TextPos::new(3, 10)));
+                assert_eq!(prev, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 10)));
+            },
+            res => { panic!("Expected a duplicate section error, got {:?}", res); },
+        }
+        assert!(matches!(parse(&scan_tokens("[rules]\ntest.nl -> example.com,\n[rules]\nexample.com -> !accept,")), Err(Error::DuplicateSectionError{ section: "rules", .. })));
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        // A half-broken config still yields the nodes that parsed fine
+        let tokens = scan_tokens("[settings]\nport: 80,\nfoo: [,\n[rules]\ntest.nl -> ,\nexample.com -> test.nl,");
+        let result: ParseResult = parse_partial(&tokens);
+        assert_eq!(result.config.config.len(), 1);
+        assert_eq!(result.config.config[0].settings.len(), 1);
+        assert_eq!(result.config.config[0].settings[0].key.value, "port");
+        assert_eq!(result.config.patterns.len(), 1);
+        assert_eq!(result.config.patterns[0].rules.len(), 1);
+        assert_eq!(result.config.range, TextRange::new(TextPos::new(1, 1), TextPos::new(6, 23)));
+        assert_eq!(result.errors.len(), 2);
+
+        // A valid config has no errors, and matches the strict parse
+        let tokens = scan_tokens("[rules]\ntest.nl -> example.com,");
+        let result: ParseResult = parse_partial(&tokens);
+        assert!(result.errors.is_empty());
+        assert_eq!(result.config, parse(&tokens).unwrap());
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        // Comments directly above an area, setting or rule are attached to it
+        let tokens = scan_borrowed_with_comments("<test>", "// Settings\n[settings] // Dropped\n// The port\n/* to listen on */\nport: 80,\naddress: \"0.0.0.0\", // Dropped too\n\n[rules]\ntest.nl -> example.com,\n// Block it\nexample.com /* dropped */ -> !accept,\n// The end\n").unwrap();
+        let config: Config = parse(&tokens).unwrap();
+        assert_eq!(config.config[0].comments, vec![ "// Settings".to_string() ]);
+        assert_eq!(config.patterns[0].comments, Vec::<String>::new());
+        assert_eq!(config.comments, vec![ "// The end".to_string() ]);
+        assert_eq!(config.config[0].settings[0].comments, vec![ "// The port".to_string(), "/* to listen on */".into() ]);
+        assert_eq!(config.config[0].settings[1].comments, Vec::<String>::new());
+        assert_eq!(config.patterns[0].rules[0].comments, Vec::<String>::new());
+        assert_eq!(config.patterns[0].rules[1].comments, vec![ "// Block it".to_string() ]);
+
+        // Without comments, we get the same AST
+        assert_eq!(parse(&scan_tokens("[settings]\nport: 80,\n// Hello\naddress: \"0.0.0.0\",")).unwrap(), parse(&scan_tokens("[settings]\nport: 80,\naddress: \"0.0.0.0\",")).unwrap());
+    }
+
+    #[test]
+    fn test_parse_area_comments() {
+        // Comments between rules are skipped, and trailing ones are not part of the area
+        let tokens = scan_borrowed_with_comments("<test>", "[rules]\n// First\na.nl -> b.nl,\n\n/* Between */\nc.nl -> d.nl,\n// Trailing\n").unwrap();
+        let config: Config = parse(&tokens).unwrap();
+        assert_eq!(config.patterns[0].rules.len(), 2);
+        assert_eq!(config.patterns[0].range, TextRange::new(TextPos::new(1, 1), TextPos::new(6, 13)));
+
+        // Same for settings
+        let tokens = scan_borrowed_with_comments("<test>", "[settings]\nport: 80, # The port\naddress: \"0.0.0.0\",\n# Trailing\n").unwrap();
+        let config: Config = parse(&tokens).unwrap();
+        assert_eq!(config.config[0].settings.len(), 2);
+        assert_eq!(config.config[0].range, TextRange::new(TextPos::new(1, 1), TextPos::new(3, 19)));
+        assert_eq!(config.range, config.config[0].range);
+    }
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
-/// Parses the toplevel Config thing.
+/// Returns whether the given token is a comment.
+#[inline]
+fn is_comment(token: &Token<SourceRef>) -> bool { matches!(token, Token::Comment(_, _)) }
+
+/// Attaches the given comments to the areas, settings and rules directly below them.
+/// 
+/// A comment is attached to an area, setting or rule if it starts on a line after the previous one (or after the area's header) and ends before it. Comments below the last area are attached to the Config itself. Any other comments (e.g., ones in the middle of a rule or after it on the same line) are dropped.
 /// 
 /// # Arguments
-/// - `input`: The input tokens to scan.
+/// - `config`: The Config to attach the comments in.
+/// - `comments`: The comment tokens to attach, in order.
+fn attach_comments(config: &mut Config, comments: &[&Token<SourceRef>]) {
+    // Collects the comments in between the given positions, where `None` means the start or end of the source text
+    let between = |after: Option<TextPos>, before: Option<TextPos>| -> Vec<String> {
+        comments.iter().filter_map(|c| {
+            let (start, end): (TextPos, TextPos) = (c.start()?, c.end()?);
+            if after.map(|a| start.line <= a.line).unwrap_or(false) || before.map(|b| end >= b).unwrap_or(false) { return None; }
+            if let Token::Comment(text, _) = c { Some(text.clone()) } else { None }
+        }).collect()
+    };
+
+    // Attach them to the settings...
+    let mut prev: Option<TextPos> = None;
+    for area in &mut config.config {
+        area.comments = between(prev, area.start());
+        prev = area.start();
+        for setting in &mut area.settings {
+            setting.comments = between(prev, setting.start());
+            prev = setting.end();
+        }
+        prev = area.end();
+    }
+    // ...to the rules...
+    for area in &mut config.patterns {
+        area.comments = between(prev, area.start());
+        prev = area.start();
+        for rule in &mut area.rules {
+            rule.comments = between(prev, rule.start());
+            prev = rule.end();
+        }
+        prev = area.end();
+    }
+    // ...and to the end of the config
+    config.comments = between(prev, None);
+}
+
+
+
+
+/***** LIBRARY *****/
+/// The result of parsing a list of tokens, which contains everything we managed to parse even if parts of it failed.
 /// 
-/// # Returns
-/// The Config if we were able to parse it.
+/// This is useful for tools that want to work with incomplete source text (e.g., for highlighting or completion). Use `parse()` instead to treat any error as fatal.
 /// 
-/// # Errors
-/// A nom error if we failed (either because no parser matched or because there was a genuine error).
-fn parse_config<'a, E: nom::error::ParseError<TokenList>>(input: &'a [Token]) -> IResult<TokenList, Config, E> {
-    comb::map(
-        branch::alt((
-            multi::many0(areas::parse_settings),
-        )),
-        |patterns: Vec<Pattern>| {
-            Config {
-                config   : vec![],
-                patterns : vec![],
-
-                range : TextRange::None,
-            }
-        },
-    )(input)
+/// Note that parsing never produces warnings; those are only found when compiling the AST (see `compiler::compile()`).
+#[derive(Debug)]
+pub struct ParseResult {
+    /// The toplevel Config node of the AST, containing all areas, settings and rules that parsed successfully.
+    pub config : Config,
+    /// The errors that occurred while parsing. If this is non-empty, `config` is incomplete.
+    pub errors : Vec<Error>,
 }
 
+impl ParseResult {
+    /// Converts this result into the Config, failing if there were any errors.
+    /// 
+    /// # Returns
+    /// The parsed Config.
+    /// 
+    /// # Errors
+    /// This function errors if any error occurred while parsing. If there are multiple, then they are returned as a `ParseError::MultipleErrors`.
+    pub fn into_result(mut self) -> Result<Config, Error> {
+        if self.errors.len() == 1 { return Err(self.errors.pop().unwrap()); }
+        if !self.errors.is_empty() { return Err(Error::MultipleErrors{ errs: self.errors }); }
+        Ok(self.config)
+    }
+}
 
 
 
+/// Parses the given list of tokens into an AST, keeping whatever we managed to parse if some of it failed.
+/// 
+/// Errors in individual settings or rules are recovered from by skipping to the next comma or section header, so that as many errors as possible are reported in one go.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens to parse.
+/// 
+/// # Returns
+/// A ParseResult with the (partial) AST and the errors we encountered.
+pub fn parse_partial<'a>(input: &'a [Token<SourceRef<'a>>]) -> ParseResult {
+    // Set the comments aside; they are attached to the settings and rules once we know where those are
+    let comments : Vec<&Token<SourceRef>> = input.iter().filter(|t| is_comment(t)).collect();
+    let tokens   : Vec<Token<SourceRef>>  = input.iter().filter(|t| !is_comment(t)).cloned().collect();
+
+    let mut input  : TokenList = TokenList::new(&tokens);
+    let mut config : Config    = Config {
+        config   : vec![],
+        patterns : vec![],
+
+        comments : vec![],
+        range    : TextRange::None,
+    };
+
+    // Parse areas until we run out of tokens
+    let mut errors : Vec<Error> = vec![];
+    let mut range  : TextRange  = TextRange::None;
+    let mut settings_header : Option<TextRange> = None;
+    let mut rules_header    : Option<TextRange> = None;
+    while !input.is_empty() {
+        match &input[0] {
+            Token::SettingsSection(_) => {
+                // Each section may occur once, and settings come first
+                let header: TextRange = input[0].range();
+                if let Some(prev) = settings_header {
+                    errors.push(Error::DuplicateSectionError{ section: "settings", range: header, prev });
+                } else if let Some(rules) = rules_header {
+                    errors.push(Error::SectionOrderError{ range: header, rules });
+                }
+                settings_header = settings_header.or(Some(header));
+
+                let (rest, area): (TokenList, SettingsArea) = areas::parse_settings(input, &mut errors);
+                range = range.join(area.range());
+                config.config.push(area);
+                input = rest;
+            },
+            Token::RulesSection(_) => {
+                let header: TextRange = input[0].range();
+                if let Some(prev) = rules_header {
+                    errors.push(Error::DuplicateSectionError{ section: "rules", range: header, prev });
+                }
+                rules_header = rules_header.or(Some(header));
+
+                let (rest, area): (TokenList, RulesArea) = areas::parse_rules(input, &mut errors);
+                range = range.join(area.range());
+                config.patterns.push(area);
+                input = rest;
+            },
+
+            token => {
+                // Anything else is an error; skip until the next section header
+                errors.push(Error::UnexpectedTokenError{ got: Box::new(token.clone().into()), expected: Box::new(Token::RulesSection(None)) });
+                let skip: usize = input.iter().position(is_section).unwrap_or(input.len());
+                input = input.take_split(skip).0;
+            },
+        }
+    }
+
+    // Done
+    config.range = range;
+    attach_comments(&mut config, &comments);
+    ParseResult {
+        config,
+        errors,
+    }
+}
 
-/***** LIBRARY *****/
 /// Parses the given list of tokens into an AST.
 /// 
+/// This is a strict version of `parse_partial()` that fails on any error.
+/// 
 /// # Arguments
 /// - `input`: The list of tokens to parse.
 /// 
@@ -63,15 +365,6 @@ fn parse_config<'a, E: nom::error::ParseError<TokenList>>(input: &'a [Token]) ->
 /// The toplevel Config node of the AST.
 /// 
 /// # Errors
-/// This function errors if we failed to parse the input.
-pub fn parse(input: Vec<Token>) -> Result<Config, Error> {
-    // Simply parse a config directly
-    match parse_config::<nom::error::VerboseError<TokenList>>(TokenList::new(input)) {
-        Ok((rest, config)) => {
-            if !rest.is_empty() { return Err(Error::NonEmptyTokenList { remain: rest }); }
-            Ok(config)
-        },
-
-        Err(err) => { return Err(Error::ParseError{ err }); },
-    }
-}
+/// This function errors if we failed to parse the input. If multiple errors occurred, then they are returned as a `ParseError::MultipleErrors`.
+#[inline]
+pub fn parse<'a>(input: &'a [Token<SourceRef<'a>>]) -> Result<Config, Error> { parse_partial(input).into_result() }
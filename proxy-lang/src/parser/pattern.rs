@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2022, 23:08:57
 //  Last edited:
-//    14 Oct 2022, 11:13:15
+//    16 Oct 2026, 19:06:09
 //  Auto updated?
 //    Yes
 // 
@@ -13,19 +13,444 @@
 //!   one.
 // 
 
+use std::num::IntErrorKind;
+use std::str::FromStr;
+
 use nom::IResult;
 use nom::{branch, combinator as comb, multi, sequence as seq};
 
 pub use crate::errors::ParseError as Error;
 use crate::spec::{Node, TextRange};
-use crate::tokens::Token;
-use crate::ast::Pattern;
+use crate::source::{SourceRef, SourceText};
+use crate::tokens::{Token, TokenList};
+use crate::ast::{Endpoint, Path, Pattern, Port, Protocol};
 use crate::parser::tag;
 
 
+// Define the shortcut for the slash-separated segments of a path (each a slash and its dot-separated labels, if any)
+type PathSegments<'a> = Vec<(TokenList<'a>, Option<Vec<(String, TextRange)>>)>;
+
+// Define the shortcut for the (optional) parts of a pattern
+type PatternParts = (Option<Protocol>, Option<Endpoint>, Option<Path>, Option<Port>);
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::spec::TextPos;
+    use crate::tests::scan_tokens;
+    use super::*;
+
+    #[test]
+    fn test_pattern() {
+        // Parse a pattern with all the parts in it
+        let tokens = scan_tokens("http://example.com/foo:8080");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.protocol, Protocol::Specific(ref p, _) if p == "http"));
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "example.com"));
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p == &vec![ "foo".to_string() ]));
+        assert!(matches!(pattern.port, Port::Specific(8080, _)));
+        assert_eq!(pattern.range, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 27)));
+
+        // Parse some longer paths
+        let tokens = scan_tokens("example.com/foo/index.html");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p == &vec![ "foo".to_string(), "index.html".to_string() ]));
+        let tokens = scan_tokens("example.com/");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p.is_empty()));
+
+        // Parse literal IP addresses
+        let tokens = scan_tokens("127.0.0.1:8080");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "127.0.0.1"));
+        assert!(matches!(pattern.port, Port::Specific(8080, _)));
+        let tokens = scan_tokens("http://[::1]:8080");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "[::1]"));
+        assert!(matches!(pattern.port, Port::Specific(8080, _)));
+        let tokens = scan_tokens("10.0.0.1.nip.io -> b.nl");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "10.0.0.1.nip.io"));
+
+        // Parse labels that start with digits
+        let tokens = scan_tokens("123-abc.nl -> b.nl");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "123-abc.nl"));
+        let tokens = scan_tokens("1-2-3-4.sslip.io:8000-8099");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "1-2-3-4.sslip.io"));
+        assert!(matches!(pattern.port, Port::Range(8000, 8099, _)));
+
+        // Stop at the end of a pattern
+        let tokens = scan_tokens("http://example.com -> :80");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert_eq!(rest.len(), 3);
+        assert_eq!(pattern.range, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 18)));
+    }
+
+    #[test]
+    fn test_pattern_wildcards() {
+        // Omitted parts are wildcards
+        let tokens = scan_tokens("*.example.com");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.protocol, Protocol::Wildcard));
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "*.example.com"));
+        assert!(matches!(pattern.path, Path::Wildcard));
+        assert!(matches!(pattern.port, Port::Wildcard));
+
+        // Explicit wildcards
+        let tokens = scan_tokens("http://*");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.base, Endpoint::Wildcard));
+        let tokens = scan_tokens("example.com:*");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.port, Port::Wildcard));
+
+        // Named wildcards are kept as-is
+        let tokens = scan_tokens("http://*1.*2");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.base, Endpoint::Specific(ref b, _) if b == "*1.*2"));
+
+        // Only a port is fine too
+        let tokens = scan_tokens(":80 ");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.protocol, Protocol::Wildcard));
+        assert!(matches!(pattern.base, Endpoint::Wildcard));
+        assert!(matches!(pattern.port, Port::Specific(80, _)));
+    }
+
+    #[test]
+    fn test_pattern_paths() {
+        // Specific paths
+        let tokens = scan_tokens("example.com/api");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p == &vec![ "api".to_string() ]));
+        let tokens = scan_tokens("example.com/");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p.is_empty()));
+
+        // Prefixes
+        let tokens = scan_tokens("example.com/api/*");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.path, Path::Prefix(ref p, _) if p == &vec![ "api".to_string() ]));
+        assert_eq!(pattern.path.range(), TextRange::new(TextPos::new(1, 12), TextPos::new(1, 17)));
+        assert!(pattern.path.matches("/api/users"));
+
+        // ...even if a block comment follows later on
+        let tokens = scan_tokens("example.com/api/* -> b.nl,\nc.nl -> d.nl, /* note */");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Prefix(ref p, _) if p == &vec![ "api".to_string() ]));
+        assert_eq!(rest.len(), 13);
+
+        // Aterisks that are not trailing or are named are still segments
+        let tokens = scan_tokens("example.com/*/info");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p == &vec![ "*".to_string(), "info".to_string() ]));
+        let tokens = scan_tokens("example.com/api/*1");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.path, Path::Specific(ref p, _) if p == &vec![ "api".to_string(), "*1".to_string() ]));
+    }
+
+    #[test]
+    fn test_pattern_port_ranges() {
+        // Parse a range of ports
+        let tokens = scan_tokens("example.com:8000-8099");
+        let (rest, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(pattern.port, Port::Range(8000, 8099, _)));
+        assert_eq!(pattern.port.range(), TextRange::new(TextPos::new(1, 12), TextPos::new(1, 21)));
+
+        // Single-port ranges are fine too
+        let tokens = scan_tokens(":80-80");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.port, Port::Range(80, 80, _)));
+
+        // The end may be separated by whitespace
+        let tokens = scan_tokens(":8000 -8099");
+        let (_, pattern): (TokenList, Pattern) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(pattern.port, Port::Range(8000, 8099, _)));
+
+        // But inverted ones are not
+        let tokens = scan_tokens("example.com:9000-8000");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::PortRangeError{ start: 9000, end: 8000, .. }))));
+        let tokens = scan_tokens("example.com:8000-99999");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalPortError{ .. }))));
+    }
+
+    #[test]
+    fn test_pattern_errors() {
+        // Nothing is not a pattern
+        assert!(matches!(parse(TokenList::new(&[])), Err(nom::Err::Error(_))));
+        let tokens = scan_tokens("->");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Error(_))));
+
+        // Ports out-of-range are hard errors
+        let tokens = scan_tokens("example.com:99999");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalPortError{ .. }))));
+        let tokens = scan_tokens("example.com:0");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalPortError{ ref raw, .. })) if raw == "0"));
+        let tokens = scan_tokens("example.com:65536");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalPortError{ ref raw, .. })) if raw == "65536"));
+        let tokens = scan_tokens("example.com:123456789012345678901234567890");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::IllegalPortError{ .. }))));
+        let tokens = scan_tokens("example.com:65535");
+        assert!(matches!(parse(TokenList::new(&tokens)).unwrap().1.port, Port::Specific(65535, _)));
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Parses the given raw value as a port number.
+/// 
+/// # Arguments
+/// - `raw`: The raw value to parse.
+/// - `source`: The source of the token that the value comes from (used for debugging).
+/// 
+/// # Returns
+/// The parsed port number.
+/// 
+/// # Errors
+/// This function errors if the value is not a number or not in the range `1..=65535`. Note that the latter includes arbitrarily long digit runs.
+pub(crate) fn parse_port_number(raw: &str, source: Option<&SourceRef>) -> Result<u16, Error> {
+    match u16::from_str(raw) {
+        Ok(0)     => Err(Error::IllegalPortError{ raw: raw.into(), source: source.map(|s| Box::new(s.into())) }),
+        Ok(value) => Ok(value),
+
+        Err(err) if *err.kind() == IntErrorKind::PosOverflow => Err(Error::IllegalPortError{ raw: raw.into(), source: source.map(|s| Box::new(s.into())) }),
+        Err(err)                                             => Err(Error::PortParseError{ raw: raw.into(), err, source: source.map(|s| Box::new(s.into())) }),
+    }
+}
+
+
+/// Parses a protocol identifier (e.g., `http://`).
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed Protocol.
+/// 
+/// # Errors
+/// This function returns an error if the top token is not a protocol.
+fn parse_protocol<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Protocol, Error> {
+    comb::map(
+        tag!(Token::Protocol, String::new()),
+        |p: TokenList<'a>| {
+            if let Token::Protocol(value, _) = &p[0] {
+                Protocol::Specific(value.clone(), p[0].range())
+            } else {
+                panic!("Got a non-Protocol token when a Protocol is the only possibility");
+            }
+        },
+    )(input)
+}
+
+/// Parses a single label of an endpoint or a path (i.e., a sequence of identifiers and/or aterisks that are not separated by dots or slashes).
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The label as a string (where aterisks are written as `*` or `*<name>`), together with its range.
+/// 
+/// # Errors
+/// This function returns an error if the top token is not an identifier or aterisk.
+fn parse_label<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, (String, TextRange), Error> {
+    comb::map(
+        multi::many1(branch::alt((
+            tag!(Token::Identifier, String::new()),
+            tag!(Token::Port, String::new()),
+            tag!(Token::Aterisk, None),
+        ))),
+        |parts: Vec<TokenList<'a>>| {
+            // Merge all of the parts together, taking note of the range
+            let mut value: String = String::new();
+            for part in &parts {
+                match &part[0] {
+                    Token::Identifier(text, _) |
+                    Token::Port(text, _)       => { value.push_str(text); },
+                    Token::Aterisk(name, _)    => {
+                        value.push('*');
+                        if let Some(name) = name { value.push_str(name); }
+                    },
+
+                    _ => { panic!("Got a non-Identifier, non-Port, non-Aterisk token when those are the only possibility"); },
+                }
+            }
+
+            // Done
+            let range: TextRange = TextRange::new(parts[0][0].start(), parts[parts.len() - 1][0].end());
+            (value, range)
+        },
+    )(input)
+}
+
+/// Parses the endpoint (i.e., the hostname or IP address) of a pattern.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed Endpoint. It is only a wildcard if it consists of a single, unnamed aterisk.
+/// 
+/// # Errors
+/// This function returns an error if we failed to parse an endpoint.
+fn parse_endpoint<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Endpoint, Error> {
+    branch::alt((
+        comb::map(
+            tag!(Token::IpAddress, String::new()),
+            |ip: TokenList<'a>| {
+                if let Token::IpAddress(value, _) = &ip[0] {
+                    Endpoint::Specific(value.clone(), ip[0].range())
+                } else {
+                    panic!("Got a non-IpAddress token when an IpAddress is the only possibility");
+                }
+            },
+        ),
+        comb::map(
+            multi::separated_list1(
+                tag!(Token::Dot),
+                parse_label,
+            ),
+            |labels: Vec<(String, TextRange)>| {
+                // A single aterisk matches anything
+                if labels.len() == 1 && labels[0].0 == "*" { return Endpoint::Wildcard; }
+
+                // Otherwise, join the labels together
+                let range: TextRange = TextRange::new(labels[0].1.start(), labels[labels.len() - 1].1.end());
+                Endpoint::Specific(labels.into_iter().map(|(l, _)| l).collect::<Vec<String>>().join("."), range)
+            },
+        ),
+    ))(input)
+}
+
+/// Parses the path of a pattern (i.e., a slash-separated list of path segments).
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed Path. Note that a lone slash is parsed as an empty list of segments (i.e., the root), and that a path ending in an unnamed aterisk (e.g., `/api/*`) is parsed as a prefix of the segments before it.
+/// 
+/// # Errors
+/// This function returns an error if we failed to parse a path.
+fn parse_path<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Path, Error> {
+    comb::map(
+        multi::many1(seq::pair(
+            tag!(Token::Slash),
+            comb::opt(multi::separated_list1(
+                tag!(Token::Dot),
+                parse_label,
+            )),
+        )),
+        |segments: PathSegments<'a>| {
+            // Compute the range first (which spans from the first slash to the last slash or segment)
+            let start : TextRange = segments[0].0[0].range();
+            let end   : TextRange = match &segments[segments.len() - 1] {
+                (_, Some(labels)) => labels[labels.len() - 1].1,
+                (slash, None)     => slash[0].range(),
+            };
+            let range: TextRange = TextRange::new(start.start(), end.end());
+
+            // Collect the non-empty segments
+            let mut segments: Vec<String> = segments.into_iter().filter_map(|(_, labels)| labels.map(|labels| labels.into_iter().map(|(l, _)| l).collect::<Vec<String>>().join("."))).collect();
+
+            // A trailing aterisk matches anything deeper
+            if segments.last().map(|s| s == "*").unwrap_or(false) {
+                segments.pop();
+                return Path::Prefix(segments, range);
+            }
+            Path::Specific(segments, range)
+        },
+    )(input)
+}
+
+/// Parses the port of a pattern (i.e., a colon followed by a number, a range of numbers or an aterisk).
+/// 
+/// A range is written as `<start>-<end>`. Note that the scanner joins the two numbers into a single identifier, unless they are separated by whitespace (e.g., `8000 -8099`), in which case the `-<end>` part is a signed integer.
+/// 
+/// # Arguments
+/// - `input`: The list of tokens.
+/// 
+/// # Returns
+/// The parsed Port.
+/// 
+/// # Errors
+/// This function returns an error if we failed to parse a port. If the colon is present but the port number is not a valid port (i.e., not in `1..=65535`), or the range ends before it starts, then a failure is returned instead.
+fn parse_port<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Port, Error> {
+    let (rest, (colon, port)): (TokenList<'a>, (TokenList<'a>, TokenList<'a>)) = seq::pair(
+        tag!(Token::Colon),
+        branch::alt((
+            tag!(Token::Port, String::new()),
+            tag!(Token::Identifier, String::new()),
+            tag!(Token::UInt, String::new()),
+            tag!(Token::Aterisk, None),
+        )),
+    )(input)?;
+
+    // Attempt to parse the port number, which may already be a whole range
+    let (start, stop): (u16, Option<u16>) = match &port[0] {
+        Token::Identifier(raw, source) if raw.contains('-') => {
+            let (start, stop): (&str, &str) = raw.split_once('-').unwrap();
+            (parse_port_number(start, source.as_ref()).map_err(nom::Err::Failure)?, Some(parse_port_number(stop, source.as_ref()).map_err(nom::Err::Failure)?))
+        },
+        Token::Port(raw, source)       |
+        Token::Identifier(raw, source) |
+        Token::UInt(raw, source)       => (parse_port_number(raw, source.as_ref()).map_err(nom::Err::Failure)?, None),
+        Token::Aterisk(_, _) => { return Ok((rest, Port::Wildcard)); },
+
+        _ => { panic!("Got a non-Port, non-Identifier, non-UInt, non-Aterisk token when those are the only possibility"); },
+    };
+
+    // Otherwise, parse the end of the range as a separate token, if any
+    let (rest, end, stop): (TokenList<'a>, TokenList<'a>, u16) = match stop {
+        Some(stop) => (rest, port, stop),
+        None       => {
+            let (rest, end): (TokenList<'a>, Option<TokenList<'a>>) = comb::opt(comb::verify(
+                tag!(Token::SInt, String::new()),
+                |end: &TokenList<'a>| matches!(&end[0], Token::SInt(raw, _) if raw.len() > 1 && raw.starts_with('-') && raw[1..].chars().all(|c| c.is_ascii_digit())),
+            ))(rest)?;
+            let end: TokenList<'a> = match end {
+                Some(end) => end,
+                None      => { return Ok((rest, Port::Specific(start, TextRange::new(colon[0].start(), port[0].end())))); },
+            };
+            let stop: u16 = if let Token::SInt(raw, source) = &end[0] {
+                parse_port_number(&raw[1..], source.as_ref()).map_err(nom::Err::Failure)?
+            } else {
+                panic!("Got a non-SInt token when a SInt is the only possibility");
+            };
+            (rest, end, stop)
+        },
+    };
+
+    // Make sure the range is not empty
+    if start > stop {
+        let source: Option<Box<SourceText>> = if let (Some(lhs), Some(rhs)) = (port[0].source(), end[0].source()) { Some(Box::new((lhs + rhs).into())) } else { None };
+        return Err(nom::Err::Failure(Error::PortRangeError{ start, end: stop, source }));
+    }
+    Ok((rest, Port::Range(start, stop, TextRange::new(colon[0].start(), end[0].end()))))
+}
+
+
+
+
+
 /***** LIBRARY *****/
 /// Parses a pattern off the given list of tokens.
 /// 
+/// A pattern has the general shape `<protocol>://<endpoint>/<path>:<port>`, where every part is optional (as long as at least one is given). Omitted parts are wildcards.
+/// 
 /// # Arguments
 /// - `input`: The list of tokens.
 /// 
@@ -34,27 +459,30 @@ use crate::parser::tag;
 /// 
 /// # Errors
 /// This function returns an error if we failed to parse a pattern.
-pub fn parse<'a>(input: &'a [Token]) -> IResult<&'a [Token], Pattern, Error> {
-    comb::map(
+pub fn parse<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Pattern, Error> {
+    let (rest, (protocol, base, path, port)): (TokenList<'a>, PatternParts) = comb::verify(
         seq::tuple((
-            tag!(Token::Protocol, String::new()),
-            branch::alt((
-                tag!(Token::IpAddress, String::new(), String::new(), String::new(), String::new()),
-                multi::separated_list1(
-                    tag!(Token::Dot),
-                    tag!(Token::Identifier, String::new()),
-                ),
-            )),
+            comb::opt(parse_protocol),
+            comb::opt(parse_endpoint),
+            comb::opt(parse_path),
+            comb::opt(parse_port),
         )),
-        |(): ()| {
-            Pattern {
-                protocol : (),
-                base     : (),
-                path     : (),
-                port     : (),
-
-                range : (),
-            }
-        }
-    )(input)
+        |(protocol, base, path, port): &(Option<Protocol>, Option<Endpoint>, Option<Path>, Option<Port>)| {
+            protocol.is_some() || base.is_some() || path.is_some() || port.is_some()
+        },
+    )(input)?;
+
+    // The range is that of all the tokens we parsed
+    let n_tokens : usize     = input.len() - rest.len();
+    let range    : TextRange = TextRange::new(input[0].start(), input[n_tokens - 1].end());
+
+    // Done
+    Ok((rest, Pattern {
+        protocol : protocol.unwrap_or(Protocol::Wildcard),
+        base     : base.unwrap_or(Endpoint::Wildcard),
+        path     : path.unwrap_or(Path::Wildcard),
+        port     : port.unwrap_or(Port::Wildcard),
+
+        range,
+    }))
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    14 Oct 2022, 10:58:44
 //  Last edited:
-//    14 Oct 2022, 11:04:11
+//    16 Oct 2026, 18:36:23
 //  Auto updated?
 //    Yes
 // 
@@ -17,8 +17,8 @@ use nom::IResult;
 use nom::{combinator as comb, sequence as seq};
 
 pub use crate::errors::ParseError as Error;
-use crate::spec::{Node, TextRange};
-use crate::tokens::Token;
+use crate::spec::TextRange;
+use crate::tokens::TokenList;
 use crate::ast::{Action, Pattern, Rule};
 use crate::parser::tag;
 use crate::parser::pattern;
@@ -36,7 +36,7 @@ use crate::parser::action;
 /// 
 /// # Errors
 /// This function errors if we failed to parse one.
-pub fn parse<'a>(input: &'a [Token]) -> IResult<&'a [Token], Rule, Error> {
+pub fn parse<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Rule, Error> {
     comb::map(
         seq::tuple((
             pattern::parse,
@@ -44,12 +44,13 @@ pub fn parse<'a>(input: &'a [Token]) -> IResult<&'a [Token], Rule, Error> {
             action::parse,
             tag!(Token::Comma),
         )),
-        |(pattern, arrow, action, comma): (Pattern, &'a [Token], Action, &'a [Token])| {
-            let range: TextRange = TextRange::new(pattern.start(), comma[0].end());
+        |(pattern, _, action, comma): (Pattern, TokenList<'a>, Action, TokenList<'a>)| {
+            let range: TextRange = TextRange::from((&pattern, &comma[0]));
             Rule {
                 lhs : pattern,
                 rhs : action,
 
+                comments : vec![],
                 range,
             }
         }
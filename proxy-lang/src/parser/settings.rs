@@ -4,7 +4,7 @@
 //  Created:
 //    13 Oct 2022, 09:39:20
 //  Last edited:
-//    16 Oct 2022, 14:53:04
+//    16 Oct 2026, 18:54:08
 //  Auto updated?
 //    Yes
 // 
@@ -19,13 +19,164 @@ use nom::IResult;
 use nom::{branch, combinator as comb, multi, sequence as seq};
 
 pub use crate::errors::ParseError as Error;
-use crate::spec::{Node, TextRange};
+use crate::spec::{Node, TextPos, TextRange};
 use crate::tokens::{Token, TokenList};
 use crate::ast::{Setting, SettingKey, SettingValue};
-use crate::parser::tag;
+use crate::parser::{skip_comments, tag};
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::scanner::scan_borrowed_with_comments;
+    use crate::tests::scan_tokens;
+    use super::*;
+
+    #[test]
+    fn test_settings() {
+        // Parse some simple settings
+        let tokens = scan_tokens("port: 80,");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(setting.key.value, "port");
+        assert!(matches!(setting.value, SettingValue::UInt(80, _)));
+        assert_eq!(setting.range, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 9)));
+        let tokens = scan_tokens("secure: true,");
+        let (_, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(setting.value, SettingValue::Bool(true, _)));
+
+        // Parse some composite ones
+        let tokens = scan_tokens("ports: [ 80, 443 ],");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(setting.value, SettingValue::List(ref values, _) if values.len() == 2 && matches!(values[1], SettingValue::UInt(443, _))));
+        let tokens = scan_tokens("ports: { http: 80, https: 443, },");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(setting.value, SettingValue::Dict(ref settings, _) if settings.len() == 2 && settings[1].key.value == "https"));
+
+        // The comma after the last setting in a block is optional...
+        let tokens = scan_tokens("port: 80");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(setting.range, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 8)));
+        let tokens = scan_tokens("ports: { http: 80, https: 443 }");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(setting.value, SettingValue::Dict(ref settings, _) if settings.len() == 2));
+        let tokens = scan_tokens("port: 80 [rules]");
+        let (rest, _): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert_eq!(rest.len(), 1);
+
+        // ...but commas in between settings are not
+        let tokens = scan_tokens("port: 80 address: \"0.0.0.0\"");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Error(Error::UnexpectedTokenError{ ref expected, .. })) if matches!(**expected, Token::Comma(_))));
+        let tokens = scan_tokens("ports: { http: 80 https: 443 },");
+        assert!(parse(TokenList::new(&tokens)).is_err());
+
+        // Lists of dictionaries
+        let tokens = scan_tokens("hosts: [ { host: \"a\", port: 80, }, { host: \"b\", }, ],");
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        match &setting.value {
+            SettingValue::List(values, _) => {
+                assert_eq!(values.len(), 2);
+                assert!(matches!(values[0], SettingValue::Dict(ref settings, _) if settings.len() == 2 && settings[0].key.value == "host" && matches!(settings[0].value, SettingValue::String(ref s, _) if s == "a")));
+                assert!(matches!(values[1], SettingValue::Dict(ref settings, _) if settings.len() == 1 && matches!(settings[0].value, SettingValue::String(ref s, _) if s == "b")));
+            },
+            value => { panic!("Expected a list, got {:?}", value); },
+        }
+
+        // Nesting is limited
+        let source: String = format!("deep: {}{},", "[".repeat(MAX_NESTING), "]".repeat(MAX_NESTING));
+        let tokens = scan_tokens(&source);
+        assert!(parse(TokenList::new(&tokens)).is_ok());
+        let source: String = format!("deep: {}{},", "[".repeat(MAX_NESTING + 1), "]".repeat(MAX_NESTING + 1));
+        let tokens = scan_tokens(&source);
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::NestingDepthError{ .. }))));
+
+        // So are duplicate keys in a dictionary
+        let tokens = scan_tokens("ports: { http: 80, http: 8080, },");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::DuplicateSettingError{ ref key, range, prev })) if key == "http" && range.start() == Some(TextPos::new(1, 20)) && prev.start() == Some(TextPos::new(1, 10))));
+
+        // Integers may be given in other radices
+        let tokens = scan_tokens("mask: [ 0xFF, 0b1010, 0o17 ],");
+        let (_, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(setting.value, SettingValue::List(ref values, _) if matches!(values.as_slice(), [ SettingValue::UInt(255, _), SettingValue::UInt(10, _), SettingValue::UInt(15, _) ])));
+        let tokens = scan_tokens("mask: 0xG,");
+        assert!(matches!(parse(TokenList::new(&tokens)), Err(nom::Err::Failure(Error::UIntParseError{ ref raw, .. })) if raw == "0xG"));
+    }
+
+    #[test]
+    fn test_settings_comments() {
+        // Comments may appear between the elements of a list
+        let tokens = scan_borrowed_with_comments("<test>", "ports: [
+    // HTTP
+    80,
+    443 /* HTTPS */,
+    # Trailing
+],").unwrap();
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(setting.value, SettingValue::List(ref values, _) if matches!(values.as_slice(), [ SettingValue::UInt(80, _), SettingValue::UInt(443, _) ])));
+        let tokens = scan_borrowed_with_comments("<test>", "ports: [ /* None yet */ ],").unwrap();
+        let (_, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(matches!(setting.value, SettingValue::List(ref values, _) if values.is_empty()));
+
+        // ...and between the settings of a dictionary, even if the last comma is omitted
+        let tokens = scan_borrowed_with_comments("<test>", "ports: {
+    // Plain
+    http: 80,
+    https: 443 # Secure
+},").unwrap();
+        let (rest, setting): (TokenList, Setting) = parse(TokenList::new(&tokens)).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(setting.value, SettingValue::Dict(ref settings, _) if settings.len() == 2 && settings[0].key.value == "http" && settings[1].key.value == "https"));
+        assert_eq!(setting.value.range(), TextRange::new(TextPos::new(1, 8), TextPos::new(5, 1)));
+    }
+}
+
+
+
 
 
 /***** HELPER FUNCTIONS *****/
+/// Finds all settings in the given block that define a key that was already defined earlier in the same block.
+/// 
+/// # Arguments
+/// - `settings`: The settings in a single settings area or dictionary, in the order they were defined.
+/// 
+/// # Returns
+/// A `ParseError::DuplicateSettingError` for every duplicate definition, pointing to that definition and to the first one.
+pub(crate) fn find_duplicates(settings: &[Setting]) -> Vec<Error> {
+    let mut errors: Vec<Error> = vec![];
+    for (i, setting) in settings.iter().enumerate() {
+        if let Some(prev) = settings[..i].iter().find(|prev| prev.key.value == setting.key.value) {
+            errors.push(Error::DuplicateSettingError{ key: setting.key.value.clone(), range: setting.key.range, prev: prev.key.range });
+        }
+    }
+    errors
+}
+
+/// Splits the radix prefix (`0x`, `0o` or `0b`, in any case) off of the given integer literal.
+/// 
+/// # Arguments
+/// - `value`: The raw integer literal.
+/// 
+/// # Returns
+/// A tuple of the digits without the prefix and the radix they are in. Literals without a prefix are returned as-is, in radix 10.
+fn split_radix(value: &str) -> (&str, u32) {
+    let radix: u32 = match value.get(..2).map(|p| p.to_ascii_lowercase()).as_deref() {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        _          => { return (value, 10); },
+    };
+    (&value[2..], radix)
+}
+
+
+
 /// Parses a string's value as a SettingValue.
 /// 
 /// # Arguments
@@ -40,8 +191,8 @@ pub fn parse_string<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingV
     comb::map(
         tag!(Token::String, String::new()),
         |s: TokenList| {
-            if let Token::String(value, range) = s[0] {
-                SettingValue::String(value, range)
+            if let Token::String(value, _) = &s[0] {
+                SettingValue::String(value.clone(), s[0].range())
             } else {
                 panic!("Got a non-String token when a String is the only possibility");
             }
@@ -51,6 +202,8 @@ pub fn parse_string<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingV
 
 /// Parses an unsigned integer's value as a SettingValue.
 /// 
+/// Note that the scanner may also have scanned numbers as ports (if followed by whitespace) or as identifiers (if followed by something else), so those are accepted too as long as they are numeric. Hexadecimal (`0x`), octal (`0o`) and binary (`0b`) literals are accepted as well.
+/// 
 /// # Arguments
 /// - `input`: The list of Tokens to parse from.
 /// 
@@ -60,23 +213,32 @@ pub fn parse_string<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingV
 /// # Errors
 /// This function errors if it failed to parse a SettingValue.
 pub fn parse_uint<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> {
-    comb::map_res(
+    let (rest, i): (TokenList<'a>, TokenList<'a>) = branch::alt((
         tag!(Token::UInt, String::new()),
-        |i: TokenList<'a>| {
-            if let Token::UInt(value, range) = i[0] {
-                // Attempt to parse
-                let value: u64 = match u64::from_str(&value) {
-                    Ok(value) => value,
-                    Err(err)  => { return Err(nom::Err::Failure(Error::UIntParseError{ raw: value, err, range })); },
-                };
-
-                // Store it
-                Ok(SettingValue::UInt(value, range))
-            } else {
-                panic!("Got a non-UInt token when a UInt is the only possibility");
-            }
-        }
-    )(input)
+        tag!(Token::Port, String::new()),
+        comb::verify(
+            tag!(Token::Identifier, String::new()),
+            |i: &TokenList<'a>| matches!(&i[0], Token::Identifier(value, _) if value.chars().all(|c| c.is_ascii_digit()) || split_radix(value).1 != 10),
+        ),
+    ))(input)?;
+
+    match &i[0] {
+        Token::UInt(value, source)       |
+        Token::Port(value, source)       |
+        Token::Identifier(value, source) => {
+            // Attempt to parse
+            let (digits, radix): (&str, u32) = split_radix(value);
+            let value: u64 = match u64::from_str_radix(digits, radix) {
+                Ok(value) => value,
+                Err(err)  => { return Err(nom::Err::Failure(Error::UIntParseError{ raw: value.clone(), err, source: source.map(|s| Box::new(s.into())) })); },
+            };
+
+            // Store it
+            Ok((rest, SettingValue::UInt(value, i[0].range())))
+        },
+
+        _ => { panic!("Got a non-UInt, non-Port, non-Identifier token when those are the only possibility"); },
+    }
 }
 
 /// Parses a signed integer's value as a SettingValue.
@@ -90,27 +252,25 @@ pub fn parse_uint<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingVal
 /// # Errors
 /// This function errors if it failed to parse a SettingValue.
 pub fn parse_sint<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> {
-    comb::map_res(
-        tag!(Token::SInt, String::new()),
-        |i: TokenList<'a>| {
-            if let Token::SInt(value, range) = i[0] {
-                // Attempt to parse
-                let value: i64 = match i64::from_str(&value) {
-                    Ok(value) => value,
-                    Err(err)  => { return Err(nom::Err::Failure(Error::SIntParseError{ raw: value, err, range })); },
-                };
-
-                // Store it
-                Ok(SettingValue::SInt(value, range))
-            } else {
-                panic!("Got a non-SInt token when a SInt is the only possibility");
-            }
-        }
-    )(input)
+    let (rest, i): (TokenList<'a>, TokenList<'a>) = tag!(Token::SInt, String::new())(input)?;
+    if let Token::SInt(value, source) = &i[0] {
+        // Attempt to parse
+        let value: i64 = match i64::from_str(value) {
+            Ok(value) => value,
+            Err(err)  => { return Err(nom::Err::Failure(Error::SIntParseError{ raw: value.clone(), err, source: source.map(|s| Box::new(s.into())) })); },
+        };
+
+        // Store it
+        Ok((rest, SettingValue::SInt(value, i[0].range())))
+    } else {
+        panic!("Got a non-SInt token when a SInt is the only possibility");
+    }
 }
 
 /// Parses a boolean's value as a SettingValue.
 /// 
+/// Note that the scanner may also have scanned booleans as identifiers, so those are accepted too as long as they are `true` or `false`.
+/// 
 /// # Arguments
 /// - `input`: The list of Tokens to parse from.
 /// 
@@ -120,117 +280,216 @@ pub fn parse_sint<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingVal
 /// # Errors
 /// This function errors if it failed to parse a SettingValue.
 pub fn parse_bool<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> {
-    comb::map_res(
+    let (rest, i): (TokenList<'a>, TokenList<'a>) = branch::alt((
         tag!(Token::Bool, String::new()),
-        |i: TokenList<'a>| {
-            if let Token::SInt(value, range) = i[0] {
-                // Attempt to parse
-                let value: bool = match value.as_str() {
-                    "true"  => true,
-                    "false" => false,
-                    _       => { return Err(nom::Err::Failure(Error::BoolParseError{ raw: value, range })); },
-                };
-
-                // Store it
-                Ok(SettingValue::Bool(value, range))
-            } else {
-                panic!("Got a non-Bool token when a Bool is the only possibility");
-            }
-        }
-    )(input)
+        comb::verify(
+            tag!(Token::Identifier, String::new()),
+            |i: &TokenList<'a>| matches!(&i[0], Token::Identifier(value, _) if value == "true" || value == "false"),
+        ),
+    ))(input)?;
+
+    match &i[0] {
+        Token::Bool(value, source)       |
+        Token::Identifier(value, source) => {
+            // Attempt to parse
+            let value: bool = match value.as_str() {
+                "true"  => true,
+                "false" => false,
+                _       => { return Err(nom::Err::Failure(Error::BoolParseError{ raw: value.clone(), source: source.map(|s| Box::new(s.into())) })); },
+            };
+
+            // Store it
+            Ok((rest, SettingValue::Bool(value, i[0].range())))
+        },
+
+        _ => { panic!("Got a non-Bool, non-Identifier token when those are the only possibility"); },
+    }
 }
 
 
 
-/// Parses a list of values as a SettingValue.
+/// Parses any kind of value as a SettingValue, keeping track of how deeply nested we are.
+/// 
+/// # Arguments
+/// - `input`: The list of Tokens to parse from.
+/// - `depth`: The number of lists and/or dictionaries we are already nested in.
+/// 
+/// # Returns
+/// The SettingValue that is defined if there was one on top of the stack.
+/// 
+/// # Errors
+/// This function errors if it failed to parse a SettingValue. If the value is a list or dictionary that is nested more than `MAX_NESTING` levels deep, then a failure is returned instead.
+fn parse_value<'a>(input: TokenList<'a>, depth: usize) -> IResult<TokenList<'a>, SettingValue, Error> {
+    // Refuse to go any deeper if we've reached the limit
+    if depth >= MAX_NESTING && !input.is_empty() && matches!(&input[0], Token::LSquare(_) | Token::LCurly(_)) {
+        return Err(nom::Err::Failure(Error::NestingDepthError{ max: MAX_NESTING, source: input[0].source().map(|s| Box::new(s.into())) }));
+    }
+
+    branch::alt((
+        parse_string,
+        parse_uint,
+        parse_sint,
+        parse_bool,
+
+        |i| parse_list_nested(i, depth + 1),
+        |i| parse_dict_nested(i, depth + 1),
+    ))(input)
+}
+
+/// Implements `parse_list()` while keeping track of how deeply nested we are.
 /// 
 /// # Arguments
 /// - `input`: The list of Tokens to parse from.
+/// - `depth`: The number of lists and/or dictionaries we are nested in, including this one.
 /// 
 /// # Returns
 /// The list of values as a SettingValue that is defined if there was one on top of the stack.
 /// 
 /// # Errors
 /// This function errors if it failed to parse a SettingValue.
-pub fn parse_list<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> {
+fn parse_list_nested<'a>(input: TokenList<'a>, depth: usize) -> IResult<TokenList<'a>, SettingValue, Error> {
     comb::map(
         seq::tuple((
             tag!(Token::LSquare),
-            multi::many0(branch::alt((
-                parse_string,
-                parse_uint,
-                parse_sint,
-                parse_bool,
-            ))),
+            multi::separated_list0(
+                tag!(Token::Comma),
+                seq::delimited(skip_comments, |i| parse_value(i, depth), skip_comments),
+            ),
+            comb::opt(tag!(Token::Comma)),
+            skip_comments,
             tag!(Token::RSquare),
         )),
-        |(l, values, r): (TokenList<'a>, Vec<SettingValue>, TokenList<'a>)| {
-            SettingValue::List(values, TextRange::new(l[0].start(), r[0].end()))
+        |(l, values, _, _, r): (TokenList<'a>, Vec<SettingValue>, Option<TokenList<'a>>, (), TokenList<'a>)| {
+            SettingValue::List(values, TextRange::from((&l[0], &r[0])))
         }
     )(input)
 }
 
-/// Parses a dictionary / struct notation as a SettingsValue.
+/// Implements `parse_dict()` while keeping track of how deeply nested we are.
 /// 
 /// # Arguments
 /// - `input`: The list of Tokens to parse from.
+/// - `depth`: The number of lists and/or dictionaries we are nested in, including this one.
 /// 
 /// # Returns
 /// The set of values as nested Settings in a SettingValue.
 /// 
 /// # Errors
-/// This function errors if it failed to parse a SettingValue.
-pub fn parse_dict<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> {
-    comb::map(
-        seq::tuple((
-            tag!(Token::LCurly),
-            multi::many0(parse),
-            tag!(Token::RCurly),
-        )),
-        |(l, settings, r): (TokenList<'a>, Vec<Setting>, TokenList<'a>)| {
-            SettingValue::Dict(settings, TextRange::new(l[0].start(), r[0].end()))
-        }
-    )(input)
-}
-
-
+/// This function errors if it failed to parse a SettingValue. If the dictionary defines the same key twice, then a failure is returned instead.
+fn parse_dict_nested<'a>(input: TokenList<'a>, depth: usize) -> IResult<TokenList<'a>, SettingValue, Error> {
+    let (rest, (l, settings, r)): (TokenList<'a>, (TokenList<'a>, Vec<Setting>, TokenList<'a>)) = seq::tuple((
+        tag!(Token::LCurly),
+        seq::terminated(multi::many0(seq::preceded(skip_comments, |i| parse_nested(i, depth))), skip_comments),
+        tag!(Token::RCurly),
+    ))(input)?;
 
+    // Keys in a dictionary must be unique
+    if let Some(err) = find_duplicates(&settings).into_iter().next() { return Err(nom::Err::Failure(err)); }
+    Ok((rest, SettingValue::Dict(settings, TextRange::from((&l[0], &r[0])))))
+}
 
+/// Parses the comma that ends a setting.
+/// 
+/// The comma may be omitted if the setting is the last one in its block, i.e., if it is followed by the end of a dictionary, a section header or the end of the input. Comments in between are not considered.
+/// 
+/// # Arguments
+/// - `input`: The list of Tokens to parse from.
+/// 
+/// # Returns
+/// The comma if there was one, or `None` if it was omitted.
+/// 
+/// # Errors
+/// This function errors if there is no comma while the setting is not the last one in its block.
+fn parse_terminator<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Option<TokenList<'a>>, Error> {
+    match input.iter().find(|t| !matches!(t, Token::Comment(_, _))) {
+        None                                                                         |
+        Some(Token::RCurly(_) | Token::SettingsSection(_) | Token::RulesSection(_)) => { return Ok((input, None)); },
+        _                                                                           => {},
+    }
+    comb::map(tag!(Token::Comma), Some)(input)
+}
 
-/***** LIBRARY *****/
-/// Parses a setting in the SettingsArea.
+/// Implements `parse()` while keeping track of how deeply nested we are.
 /// 
 /// # Arguments
 /// - `input`: The list of Tokens to parse from.
+/// - `depth`: The number of lists and/or dictionaries this setting is nested in.
 /// 
 /// # Returns
 /// The Setting that is defined if there was one on top of the stack.
 /// 
 /// # Errors
 /// This function errors if we could find one on top of the stack.
-pub fn parse<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Setting, Error> {
+fn parse_nested<'a>(input: TokenList<'a>, depth: usize) -> IResult<TokenList<'a>, Setting, Error> {
     comb::map(
         seq::tuple((
             tag!(Token::Identifier, String::new()),
             tag!(Token::Colon),
-            branch::alt((
-                parse_string,
-                parse_uint,
-                parse_sint,
-                parse_bool,
-
-                parse_list,
-                parse_dict,
-            )),
-            tag!(Token::Comma),
+            |i| parse_value(i, depth),
+            parse_terminator,
         )),
-        |(key, colon, value, comma): (TokenList<'a>, TokenList<'a>, SettingValue, TokenList<'a>)| {
+        |(key, _, value, comma): (TokenList<'a>, TokenList<'a>, SettingValue, Option<TokenList<'a>>)| {
+            let end: Option<TextPos> = if let Some(comma) = comma { comma[0].end() } else { value.end() };
             Setting {
-                key   : if let Token::Identifier(key, range) = key[0] { SettingKey{ value: key, range } } else { panic!("Got a non-Identifier even when that should be the only possibility") },
+                key   : if let Token::Identifier(value, _) = &key[0] { SettingKey{ value: value.clone(), range: key[0].range() } } else { panic!("Got a non-Identifier even when that should be the only possibility") },
                 value,
 
-                range : TextRange::new(key[0].start(), comma[0].end()),
+                comments : vec![],
+                range    : TextRange::new(key[0].start(), end),
             }
         },
     )(input)
 }
+
+
+
+/// Parses a list of values as a SettingValue.
+/// 
+/// The values may be of any kind, including nested lists and dictionaries (up to `MAX_NESTING` levels deep).
+/// 
+/// # Arguments
+/// - `input`: The list of Tokens to parse from.
+/// 
+/// # Returns
+/// The list of values as a SettingValue that is defined if there was one on top of the stack.
+/// 
+/// # Errors
+/// This function errors if it failed to parse a SettingValue.
+#[inline]
+pub fn parse_list<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> { parse_list_nested(input, 1) }
+
+/// Parses a dictionary / struct notation as a SettingsValue.
+/// 
+/// # Arguments
+/// - `input`: The list of Tokens to parse from.
+/// 
+/// # Returns
+/// The set of values as nested Settings in a SettingValue.
+/// 
+/// # Errors
+/// This function errors if it failed to parse a SettingValue. If the dictionary defines the same key twice, then a failure is returned instead.
+#[inline]
+pub fn parse_dict<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, SettingValue, Error> { parse_dict_nested(input, 1) }
+
+
+
+
+
+/***** LIBRARY *****/
+/// The maximum number of levels that lists and dictionaries may be nested in a setting's value, to avoid overflowing the stack on pathological input.
+pub const MAX_NESTING: usize = 64;
+
+
+
+/// Parses a setting in the SettingsArea.
+/// 
+/// # Arguments
+/// - `input`: The list of Tokens to parse from.
+/// 
+/// # Returns
+/// The Setting that is defined if there was one on top of the stack.
+/// 
+/// # Errors
+/// This function errors if we could find one on top of the stack.
+#[inline]
+pub fn parse<'a>(input: TokenList<'a>) -> IResult<TokenList<'a>, Setting, Error> { parse_nested(input, 0) }
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2022, 20:54:53
 //  Last edited:
-//    04 Nov 2022, 08:20:59
+//    16 Oct 2026, 19:02:50
 //  Auto updated?
 //    Yes
 // 
@@ -12,7 +12,7 @@
 //!   Implements nom functions for scanning comments.
 // 
 
-use nom::IResult;
+use nom::{IResult, InputTake as _};
 use nom::{branch, bytes::complete as bc, combinator as comb, multi, sequence as seq};
 
 use crate::scanner::Input;
@@ -21,19 +21,37 @@ use crate::scanner::Input;
 /***** TESTS *****/
 #[cfg(test)]
 mod tests {
-    use crate::tests::assert_scan;
     use super::*;
 
     #[test]
     fn test_comments() {
         // Simply attempt to parse some comment stuff
-        assert_scan!(scan::<nom::error::Error<Input>>, "// Hello there!", 15);
-        assert_scan!(scan::<nom::error::Error<Input>>, "/* Hello there! */", 18);
+        assert_eq!(scan::<nom::error::Error<Input>>(Input::new("<test>", "// Hello there!")).unwrap().1.as_str(), "// Hello there!");
+        assert_eq!(scan::<nom::error::Error<Input>>(Input::new("<test>", "/* Hello there! */")).unwrap().1.as_str(), "/* Hello there! */");
+
+        // The newline ending a single-line comment is consumed but not part of the comment
+        let (r, c) = scan::<nom::error::Error<Input>>(Input::new("<test>", "// Hello there!\nfoo")).unwrap();
+        assert_eq!(c.as_str(), "// Hello there!");
+        assert_eq!(r.as_str(), "foo");
+
+        // Hash comments work the same
+        assert_eq!(scan::<nom::error::Error<Input>>(Input::new("<test>", "# Hello there!")).unwrap().1.as_str(), "# Hello there!");
+        let (r, c) = scan::<nom::error::Error<Input>>(Input::new("<test>", "# Hello there!\nfoo")).unwrap();
+        assert_eq!(c.as_str(), "# Hello there!");
+        assert_eq!(r.as_str(), "foo");
 
         // Parse a multiple comment
-        let (r, _) = scan::<nom::error::Error<Input>>(Input::new("<test>", "// Hello there!\n/* Hello there! */")).unwrap();
+        let source: &str = "// Hello there!\n/* Hello there! */";
+        let (r, _) = scan::<nom::error::Error<Input>>(Input::new("<test>", source)).unwrap();
         let (r, _) = scan::<nom::error::Error<Input>>(r).unwrap();
-        assert_eq!(r, unsafe{ Input::new_with_raw_offset("<test>", "", 34, 0) });
+        assert_eq!(r, unsafe{ Input::new_with_raw_offset("<test>", source, 34, 0) });
+
+        // Block comments do not start right after a word or an aterisk (e.g., at the end of a path)
+        let source: &str = "a.nl/api/* -> b.nl, /* note */";
+        assert!(scan::<nom::error::Error<Input>>(unsafe{ Input::new_with_raw_offset("<test>", source, 8, 22) }).is_err());
+        assert!(scan::<nom::error::Error<Input>>(unsafe{ Input::new_with_raw_offset("<test>", source, 20, 10) }).is_ok());
+        let source: &str = "*/* note */";
+        assert!(scan::<nom::error::Error<Input>>(unsafe{ Input::new_with_raw_offset("<test>", source, 1, 10) }).is_err());
     }
 }
 
@@ -42,21 +60,18 @@ mod tests {
 
 
 /***** HELPER FUNCTIONS *****/
-/// Scans a comment starting with '//'.
+/// Scans a comment that starts with the given marker and runs until the end of the line.
 /// 
 /// # Arguments
-/// - `input`: The Input to scan.
+/// - `marker`: The marker that starts the comment (e.g., `//`).
 /// 
 /// # Returns
-/// Nothing on success (since we don't wanna parse comments).
-/// 
-/// # Errors
-/// This function may error if nom failed to scan a comment.
-fn scan_singleline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, (), E> {
-    comb::value(
-        (),
-        seq::preceded(
-            bc::tag("//"),
+/// A function that scans the comment. On success, it returns the text of the comment, including the marker but excluding the newline that ends it (which is consumed).
+fn scan_line<'a, E: nom::error::ParseError<Input<'a>>>(marker: &'static str) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, Input<'a>, E> {
+    move |input: Input<'a>| {
+        // Scan until the end of the line
+        let (rest, _): (Input<'a>, _) = seq::preceded(
+            bc::tag(marker),
             multi::many_till(
                 seq::pair(
                     comb::not(branch::alt((
@@ -65,45 +80,83 @@ fn scan_singleline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -
                     ))),
                     bc::take(1usize),
                 ),
-                branch::alt((
+                comb::peek(branch::alt((
                     bc::tag("\n"),
                     comb::eof,
-                )),
+                ))),
             ),
-        ),
-    )(input)
+        )(input)?;
+        let comment: Input<'a> = input.take(rest.offset() - input.offset());
+
+        // Consume the newline too
+        let (rest, _): (Input<'a>, Input<'a>) = branch::alt((
+            bc::tag("\n"),
+            comb::eof,
+        ))(rest)?;
+        Ok((rest, comment))
+    }
 }
 
+/// Scans a comment starting with '//'.
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The text of the comment, including the `//` but excluding the newline that ends it (which is consumed).
+/// 
+/// # Errors
+/// This function may error if nom failed to scan a comment.
+#[inline]
+fn scan_singleline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Input<'a>, E> { scan_line("//")(input) }
+
+/// Scans a comment starting with '#' (as in YAML or shell scripts).
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The text of the comment, including the `#` but excluding the newline that ends it (which is consumed).
+/// 
+/// # Errors
+/// This function may error if nom failed to scan a comment.
+#[inline]
+fn scan_hash<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Input<'a>, E> { scan_line("#")(input) }
+
 /// Scans a comment starting with '/*' and ending with `*/` (multiline).
 /// 
+/// The comment may not start directly after a word or an aterisk, since `/*` then ends a path instead (e.g., `example.com/api/*`).
+/// 
 /// # Arguments
 /// - `input`: The Input to scan.
 /// 
 /// # Returns
-/// Nothing on success (since we don't wanna parse comments).
+/// The text of the comment, including the `/*` and `*/`.
 /// 
 /// # Errors
 /// This function may error if nom failed to scan a comment.
-fn scan_multiline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, (), E> {
-    comb::value(
-        (),
-        seq::preceded(
-            bc::tag("/*"),
-            multi::many_till(
-                seq::pair(
-                    comb::not(bc::tag("*/")),
-                    bc::take(1usize),
-                ),
-                bc::tag("*/"),
+fn scan_multiline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Input<'a>, E> {
+    // Make sure we're not in the middle of a pattern
+    if input.source()[..input.offset()].chars().next_back().map(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '*').unwrap_or(false) {
+        return Err(nom::Err::Error(E::from_error_kind(input, nom::error::ErrorKind::Verify)));
+    }
+
+    let (rest, _): (Input<'a>, _) = seq::preceded(
+        bc::tag("/*"),
+        multi::many_till(
+            seq::pair(
+                comb::not(bc::tag("*/")),
+                bc::take(1usize),
             ),
+            bc::tag("*/"),
         ),
-    )(input)
+    )(input)?;
+    Ok((rest, input.take(rest.offset() - input.offset())))
 }
 
 
 
 
-
 /***** LIBRARY *****/
 /// Scans one of the possible comments.
 /// 
@@ -111,13 +164,14 @@ fn scan_multiline<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) ->
 /// - `input`: The Input to scan.
 /// 
 /// # Returns
-/// Nothing on success (since we don't wanna parse comments).
+/// The text of the comment, including its markers.
 /// 
 /// # Errors
 /// This function may error if nom failed to scan a comment.
-pub fn scan<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, (), E> {
+pub fn scan<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Input<'a>, E> {
     branch::alt((
         scan_singleline,
+        scan_hash,
         scan_multiline,
     ))(input)
 }
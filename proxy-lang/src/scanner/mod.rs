@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2022, 20:31:32
 //  Last edited:
-//    22 Oct 2022, 14:56:04
+//    16 Oct 2026, 18:17:01
 //  Auto updated?
 //    Yes
 // 
@@ -22,7 +22,7 @@ pub mod values;
 pub mod scanner;
 
 // Pull stuff into the global namespace
-pub use scanner::{scan, Error};
+pub use scanner::{scan, scan_borrowed, scan_borrowed_with_comments, scan_file, scan_str, Error};
 
 
 // Define the shortcut for the scanner input
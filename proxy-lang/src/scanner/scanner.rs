@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2022, 20:45:32
 //  Last edited:
-//    22 Oct 2022, 15:57:25
+//    16 Oct 2026, 19:06:09
 //  Auto updated?
 //    Yes
 // 
@@ -12,9 +12,11 @@
 //!   Implements the toplevel scanning functions.
 // 
 
-use std::io::Read;
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
 
-use nom::IResult;
+use nom::{IResult, InputTake as _};
 use nom::{branch, combinator as comb};
 
 pub use crate::errors::ScanError as Error;
@@ -30,17 +32,25 @@ use crate::scanner::values;
 /***** TESTS *****/
 #[cfg(test)]
 mod tests {
-    use crate::spec::Node;
+    use std::time::{Duration, Instant};
+
     use crate::tests::run_test_on_files;
     use super::*;
 
 
+    /// Generates a config with a few hundred rules in it.
+    fn large_source() -> String {
+        let mut source: String = "[settings]\nports: [ 80, 443 ],\n\n[rules]\n".into();
+        for i in 0..200 { source.push_str(&format!("host{}.example.com -> backend{}.example.com:8080,\n", i, i)); }
+        source
+    }
+
     /// Runs tests on the files in the tests folder.
     #[test]
     fn test_files() {
         run_test_on_files(|path, source| {
             // Run the scanner
-            let tokens: Vec<crate::tokens::Token<SourceText>> = match scan(&format!("{}", path.display()), source.as_bytes()) {
+            let tokens: Vec<crate::tokens::Token<SourceText>> = match scan(format!("{}", path.display()), source.as_bytes()) {
                 Ok(tokens) => tokens,
                 Err(err)   => { panic!("Scanner failed: {}", err); },
             };
@@ -57,13 +67,193 @@ mod tests {
             println!();
         });
     }
+
+    #[test]
+    fn test_unterminated_string() {
+        // An unterminated string points at its opening quote
+        match scan("<test>", "name: \"unterminated,\nport: 42,".as_bytes()) {
+            Err(Error::UnterminatedStringError{ source }) => {
+                assert_eq!(source.start(), (1, 7));
+                assert_eq!(source.end(), (1, 7));
+                assert_eq!(source.as_str(), "\"");
+            },
+            res => { panic!("Expected an unterminated string error, got {:?}", res); },
+        }
+
+        // Escaped quotes do not terminate it either
+        assert!(matches!(scan("<test>", "\"foo\\\"".as_bytes()), Err(Error::UnterminatedStringError{ .. })));
+
+        // But other errors are left alone
+        assert!(matches!(scan("<test>", "\"\\q\"".as_bytes()), Err(Error::ScanError{ .. })));
+    }
+
+    #[test]
+    fn test_comments() {
+        // Comments of all kinds are skipped
+        for source in [ "# A comment\nport: 80,", "port: 80, # A comment", "port: 80, // A comment\n", "/* A\ncomment */ port: 80," ] {
+            let tokens: Vec<crate::tokens::Token<SourceText>> = scan("<test>", source.as_bytes()).unwrap();
+            assert_eq!(tokens.len(), 4, "for source {:?}", source);
+        }
+
+        // Unless asked for
+        let tokens: Vec<Token> = scan_borrowed_with_comments("<test>", "# A comment\nport: 80, # Another").unwrap();
+        assert_eq!(tokens.len(), 6);
+        assert!(matches!(&tokens[0], Token::Comment(text, _) if text == "# A comment"));
+        assert!(matches!(&tokens[5], Token::Comment(text, _) if text == "# Another"));
+    }
+
+    #[test]
+    fn test_scan_borrowed() {
+        // Scan a large config both ways
+        let source: String = large_source();
+        let borrowed: Vec<Token> = scan_borrowed("<test>", &source).unwrap();
+        let owned: Vec<crate::tokens::Token<SourceText>> = scan_str("<test>", &source).unwrap();
+
+        // They have the same tokens, which can be converted on demand
+        assert_eq!(borrowed.len(), owned.len());
+        for (borrowed, owned) in borrowed.iter().zip(owned.iter()) {
+            let converted: crate::tokens::Token<SourceText> = borrowed.clone().into();
+            assert_eq!(converted.source().as_ref().map(|s| s.start()), owned.source().as_ref().map(|s| s.start()));
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with `--ignored --nocapture` to see the timings"]
+    fn bench_scan_borrowed() {
+        // Not converting should be (much) cheaper, since every conversion walks the source text up to the token
+        let source: String = large_source();
+        let start: Instant = Instant::now();
+        let borrowed: Vec<Token> = scan_borrowed("<test>", &source).unwrap();
+        let borrowed_time: Duration = start.elapsed();
+        let start: Instant = Instant::now();
+        let owned: Vec<crate::tokens::Token<SourceText>> = scan_str("<test>", &source).unwrap();
+        let owned_time: Duration = start.elapsed();
+        println!("Scanned {} tokens borrowed in {:?} and {} owned in {:?}", borrowed.len(), borrowed_time, owned.len(), owned_time);
+    }
+
+    #[test]
+    fn test_utf8() {
+        // Invalid UTF-8 gets a dedicated error that tells where it is
+        match scan("<test>", &b"port: \xFF\xFE,"[..]) {
+            Err(Error::Utf8Error{ file, offset }) => {
+                assert_eq!(file, "<test>");
+                assert_eq!(offset, 6);
+            },
+            res => { panic!("Expected a UTF-8 error, got {:?}", res); },
+        }
+
+        // Sources larger than a single chunk are read completely
+        let source: String = format!("{}port: 80,", " ".repeat(3 * READ_CHUNK_SIZE));
+        assert_eq!(scan("<test>", source.as_bytes()).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_scan_str() {
+        // Scanning a string gives the same tokens as scanning a reader
+        let source: &str = "[settings]\nports: [ 80, 443 ],\n";
+        let tokens: Vec<crate::tokens::Token<SourceText>> = scan_str("<test>", source).unwrap();
+        assert_eq!(tokens, scan("<test>", source.as_bytes()).unwrap());
+        assert_eq!(tokens.len(), 9);
+        assert_eq!(tokens[0].source().as_ref().unwrap().name(), "<test>");
+
+        // Same for files
+        let path: String = format!("{}/compile.pconf", crate::tests::TEST_DIR);
+        assert_eq!(scan_file(&path).unwrap(), scan_str(&path, std::fs::read_to_string(&path).unwrap()).unwrap());
+        assert!(matches!(scan_file(format!("{}/does_not_exist.pconf", crate::tests::TEST_DIR)), Err(Error::ReaderReadError{ .. })));
+    }
 }
 
 
 
 
 
+/***** CONSTANTS *****/
+/// The size (in bytes) of the chunks in which we read the source text.
+const READ_CHUNK_SIZE: usize = 8192;
+
+
+
+
+
 /***** HELPER FUNCTIONS *****/
+/// Reads the given reader to a string in chunks.
+/// 
+/// # Arguments
+/// - `file`: Some name / path that the user can use to identify the given reader.
+/// - `reader`: The reader to read from.
+/// - `capacity`: The number of bytes to reserve up front (e.g., the size of the file, if known).
+/// 
+/// # Returns
+/// The contents of the reader as a string.
+/// 
+/// # Errors
+/// This function errors if we failed to read from the reader or if its contents are not valid UTF-8.
+fn read_source(file: &str, reader: impl Read, capacity: usize) -> Result<String, Error> {
+    let mut reader = reader;
+
+    // Read chunks until there are no more
+    let mut bytes : Vec<u8>               = Vec::with_capacity(capacity);
+    let mut chunk : [u8; READ_CHUNK_SIZE] = [0; READ_CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0)    => { break; },
+            Ok(n)    => { bytes.extend_from_slice(&chunk[..n]); },
+            Err(err) => {
+                if err.kind() == ErrorKind::Interrupted { continue; }
+                return Err(Error::ReaderReadError{ file: file.into(), err });
+            },
+        }
+    }
+
+    // Make sure it's valid UTF-8
+    match String::from_utf8(bytes) {
+        Ok(source) => Ok(source),
+        Err(err)   => Err(Error::Utf8Error{ file: file.into(), offset: err.utf8_error().valid_up_to() }),
+    }
+}
+
+/// Scans the given source text as a stream of tokens that borrow from it.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to scan.
+/// - `keep_comments`: Whether to emit `Token::Comment`s or to filter them out.
+/// 
+/// # Returns
+/// The vector of Tokens that are parsed.
+/// 
+/// # Errors
+/// This function errors if the input was ill-formed.
+fn scan_source<'a>(name: &'a str, source: &'a str, keep_comments: bool) -> Result<Vec<Token<'a>>, Error> {
+    // Parse tokens until eof
+    let mut input  : SourceRef  = SourceRef::new(name, source);
+    let mut tokens : Vec<Token> = vec![];
+    while !input.is_empty() {
+        // Parse it
+        match scan_token::<nom::error::VerboseError<SourceRef>>(input) {
+            Ok((rest, Some(token))) => {
+                if keep_comments || !matches!(token, Token::Comment(_, _)) { tokens.push(token); }
+                input = rest;
+            },
+            Ok((rest, None))        => {
+                input = rest;
+            },
+
+            Err(err) => {
+                // Point at the opening quote if the user forgot to close a string, since that's a common mistake
+                if values::is_unterminated_string(input) {
+                    return Err(Error::UnterminatedStringError{ source: input.take(1).into() });
+                }
+                return Err(Error::ScanError{ err: format!("{}", err) });
+            },
+        }
+    }
+
+    // Done, return the list
+    Ok(tokens)
+}
+
+
 /// Scans a single token.
 /// 
 /// # Arguments
@@ -74,28 +264,33 @@ mod tests {
 /// 
 /// # Errors
 /// A nom error if we failed (either because no parser matched or because there was a genuine error).
-fn scan_token<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Option<Token<'a>>, E> {
+pub(crate) fn scan_token<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Option<Token<'a>>, E> {
     branch::alt((
         comb::value(
             None,
             whitespace::scan,
         ),
-        comb::value(
-            None,
+        comb::map(
             comments::scan,
+            |c: Input<'a>| Some(Token::Comment(c.as_str().into(), Some(c))),
         ),
 
+        // Note that keywords go before punctuation, since otherwise `[settings]` would be scanned as `[`, `settings`, `]` (and the same goes for IPv6 addresses like `[::1]`)
         comb::map(
-            punctuation::scan,
-            |p| Some(p),
+            keywords::scan,
+            Some,
         ),
         comb::map(
-            keywords::scan,
-            |k| Some(k),
+            values::scan_ipv6address,
+            Some,
+        ),
+        comb::map(
+            punctuation::scan,
+            Some,
         ),
         comb::map(
             values::scan,
-            |v| Some(v),
+            Some,
         ),
     ))(input)
 }
@@ -105,8 +300,60 @@ fn scan_token<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRe
 
 
 /***** LIBRARY *****/
+/// Parse the given source text as a stream of tokens that borrow from it.
+/// 
+/// Unlike `scan()`, the tokens refer to the source text by `SourceRef`, which is what the parser expects. Comments are filtered out.
+/// 
+/// This is also much cheaper than `scan()`, since converting a token to a `Token<SourceText>` has to walk the source text to find its line and column. Prefer this function if you don't need the tokens to outlive the source text; single tokens can still be converted on demand (e.g., when reporting an error) using `Token::into()`.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to scan.
+/// 
+/// # Returns
+/// The vector of Tokens that are parsed.
+/// 
+/// # Errors
+/// This function errors if the input was ill-formed.
+#[inline]
+pub fn scan_borrowed<'a>(name: &'a str, source: &'a str) -> Result<Vec<Token<'a>>, Error> { scan_source(name, source, false) }
+
+/// Parse the given source text as a stream of tokens that borrow from it, including any comments.
+/// 
+/// Comments are emitted as `Token::Comment`s. The parser skips them, but attaches any comments directly above a setting or rule to it.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to scan.
+/// 
+/// # Returns
+/// The vector of Tokens that are parsed.
+/// 
+/// # Errors
+/// This function errors if the input was ill-formed.
+#[inline]
+pub fn scan_borrowed_with_comments<'a>(name: &'a str, source: &'a str) -> Result<Vec<Token<'a>>, Error> { scan_source(name, source, true) }
+
 /// Parse the given source text as a stream of tokens.
 /// 
+/// This is a convenience version of `scan()` for when the source text is already in memory.
+/// 
+/// # Arguments
+/// - `name`: Some name / path that the user can use to identify the source text.
+/// - `source`: The source text to scan.
+/// 
+/// # Returns
+/// The vector of Tokens that are parsed.
+/// 
+/// # Errors
+/// This function errors if the input was ill-formed.
+#[inline]
+pub fn scan_str(name: impl AsRef<str>, source: impl AsRef<str>) -> Result<Vec<crate::tokens::Token<SourceText>>, Error> {
+    Ok(scan_borrowed(name.as_ref(), source.as_ref())?.into_iter().map(|t| t.into()).collect())
+}
+
+/// Parse the given reader as a stream of tokens.
+/// 
 /// # Arguments
 /// - `file`: Some name / path that the user can use to identify the given reader.
 /// - `reader`: The reader that contains the source text to read from.
@@ -115,35 +362,37 @@ fn scan_token<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRe
 /// The vector of Tokens that are parsed.
 /// 
 /// # Errors
-/// This function errors if the input was ill-formed.
+/// This function errors if we failed to read the reader, if it is not valid UTF-8 or if the input was ill-formed.
 pub fn scan(file: impl AsRef<str>, reader: impl Read) -> Result<Vec<crate::tokens::Token<SourceText>>, Error> {
     let file: &str = file.as_ref();
-    let mut reader = reader;
-
-    // Consume the reader to string
-    let mut source: String = String::new();
-    if let Err(err) = reader.read_to_string(&mut source) {
-        return Err(Error::ReaderReadError{ file: file.into(), err });
-    }
+    let source: String = read_source(file, reader, 0)?;
+    scan_str(file, source)
+}
 
-    // Parse tokens until eof
-    let mut input  : SourceRef  = SourceRef::new(file, &source);
-    let mut tokens : Vec<Token> = vec![];
-    while !input.is_empty() {
-        // Parse it
-        match scan_token::<nom::error::VerboseError<SourceRef>>(input) {
-            Ok((rest, Some(token))) => {
-                tokens.push(token);
-                input = rest;
-            },
-            Ok((rest, None))        => {
-                input = rest;
-            },
+/// Parse the given file as a stream of tokens.
+/// 
+/// The buffer for the source text is pre-sized to the file's length, so it is read without re-allocations.
+/// 
+/// # Arguments
+/// - `path`: The path of the file to read.
+/// 
+/// # Returns
+/// The vector of Tokens that are parsed.
+/// 
+/// # Errors
+/// This function errors if we failed to open or read the file, if it is not valid UTF-8 or if the input was ill-formed.
+pub fn scan_file(path: impl AsRef<Path>) -> Result<Vec<crate::tokens::Token<SourceText>>, Error> {
+    let path: &Path  = path.as_ref();
+    let file: String = path.display().to_string();
 
-            Err(err) => { return Err(Error::ScanError{ err: format!("{}", err) }); },
-        }
-    }
+    // Open the file and find out how large it is
+    let handle: File = match File::open(path) {
+        Ok(handle) => handle,
+        Err(err)   => { return Err(Error::ReaderReadError{ file, err }); },
+    };
+    let size: usize = handle.metadata().map(|m| m.len() as usize).unwrap_or(0);
 
-    // Done, return the list
-    Ok(tokens.into_iter().map(|t| t.into()).collect())
+    // Read it, then scan it
+    let source: String = read_source(&file, handle, size)?;
+    scan_str(file, source)
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    11 Oct 2022, 13:25:46
 //  Last edited:
-//    22 Oct 2022, 15:57:13
+//    16 Oct 2026, 19:06:09
 //  Auto updated?
 //    Yes
 // 
@@ -12,6 +12,9 @@
 //!   Parses specific value tokens such as ports or path parts.
 // 
 
+use std::net::Ipv6Addr;
+use std::str::FromStr;
+
 use nom::IResult;
 use nom::{branch, bytes::complete as bc, character::complete as cc, combinator as comb, multi, sequence as seq};
 
@@ -20,10 +23,13 @@ use crate::scanner::{Input, Token};
 use crate::scanner::whitespace as ws;
 
 
+// Define the shortcut for a word followed by any dash-joined words (each preceded by its dashes)
+type DashedWords<'a> = (Vec<Input<'a>>, Vec<(Vec<Input<'a>>, Vec<Input<'a>>)>);
+
+
 /***** TESTS *****/
 #[cfg(test)]
 mod tests {
-    use crate::source::SourceText;
     // use crate::tests::{input, range};
     use super::*;
 
@@ -84,6 +90,142 @@ mod tests {
         //     crate::tokens::Token::<SourceText>::Port("42".into(), range!(5:19 - 5:20)),
         // ]);
     }
+
+    #[test]
+    fn test_aterisk() {
+        // Scan aterisks at the very end of the input
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "*")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::Aterisk(None, Some(ref s)) if s.as_str() == "*"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "*1")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::Aterisk(Some(ref n), Some(ref s)) if n == "1" && s.as_str() == "*1"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "**")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::Aterisk(None, Some(ref s)) if s.as_str() == "**"));
+
+        // And somewhere in the middle of it
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "*12")).unwrap();
+        assert_eq!(rest.as_str(), "2");
+        assert!(matches!(token, Token::Aterisk(Some(ref n), Some(ref s)) if n == "1" && s.as_str() == "*1"));
+    }
+
+    #[test]
+    fn test_string() {
+        // Scan some plain strings & simple escapes
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "\"Hello there!\"")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::String(ref s, _) if s == "Hello there!"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "\"Tes\\\"t\\n\\\\\"")).unwrap();
+        assert!(matches!(token, Token::String(ref s, _) if s == "Tes\"t\n\\"));
+
+        // Scan some hexadecimal escapes
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "\"\\x41BC\"")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::String(ref s, _) if s == "ABC"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "\"Smile \\u{1F600}!\"")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::String(ref s, _) if s == "Smile \u{1F600}!"));
+
+        // Invalid escapes do not scan
+        assert!(scan::<nom::error::Error<Input>>(Input::new("<test>", "\"\\u{110000}\"")).is_err());
+        assert!(scan::<nom::error::Error<Input>>(Input::new("<test>", "\"\\u{D800}\"")).is_err());
+        assert!(scan::<nom::error::Error<Input>>(Input::new("<test>", "\"\\xG1\"")).is_err());
+        assert!(scan::<nom::error::Error<Input>>(Input::new("<test>", "\"\\q\"")).is_err());
+    }
+
+    #[test]
+    fn test_ipaddress() {
+        // Scan some valid addresses
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "127.0.0.1")).unwrap();
+        assert!(rest.is_empty());
+        assert!(matches!(token, Token::IpAddress(ref ip, _) if ip == "127.0.0.1"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "255.255.255.255 -> :80")).unwrap();
+        assert_eq!(rest.as_str(), " -> :80");
+        assert!(matches!(token, Token::IpAddress(ref ip, _) if ip == "255.255.255.255"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "10.0.0.1:8080")).unwrap();
+        assert_eq!(rest.as_str(), ":8080");
+        assert!(matches!(token, Token::IpAddress(ref ip, _) if ip == "10.0.0.1"));
+
+        // Boundaries are left to the other scanners
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "999.1.1.1")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "999"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "256.1.1.1")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "256"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "1.2.3")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "1"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "1.2.3.4abc")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "1"));
+
+        // So are addresses that continue as a hostname
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "10.0.0.1.nip.io")).unwrap();
+        assert_eq!(rest.as_str(), ".0.0.1.nip.io");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "10"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "10.0.0.1-a.nip.io")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "10"));
+
+        // IPv6 addresses are given in brackets
+        let (rest, token): (Input, Token) = scan_ipv6address::<nom::error::Error<Input>>(Input::new("<test>", "[::1]:8080")).unwrap();
+        assert_eq!(rest.as_str(), ":8080");
+        assert!(matches!(token, Token::IpAddress(ref ip, Some(ref s)) if ip == "[::1]" && s.as_str() == "[::1]"));
+        let (_, token): (Input, Token) = scan_ipv6address::<nom::error::Error<Input>>(Input::new("<test>", "[2001:db8::ffff:10.0.0.1]")).unwrap();
+        assert!(matches!(token, Token::IpAddress(ref ip, _) if ip == "[2001:db8::ffff:10.0.0.1]"));
+        assert!(scan_ipv6address::<nom::error::Error<Input>>(Input::new("<test>", "[80]")).is_err());
+        assert!(scan_ipv6address::<nom::error::Error<Input>>(Input::new("<test>", "[ ::1 ]")).is_err());
+    }
+
+    #[test]
+    fn test_radix_uint() {
+        // Prefixed integers are kept as-is, in any case
+        for raw in [ "0xFF", "0XfF", "0o17", "0b1010" ] {
+            let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", raw)).unwrap();
+            assert!(rest.is_empty());
+            assert!(matches!(token, Token::UInt(ref value, Some(ref s)) if value == raw && s.as_str() == raw));
+        }
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "0xFF,")).unwrap();
+        assert_eq!(rest.as_str(), ",");
+        assert!(matches!(token, Token::UInt(ref value, _) if value == "0xFF"));
+
+        // Anything that runs on is left to the identifier scanner
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "0xG")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "0xG"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "0b12")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "0b12"));
+        let (_, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "0b10.nl")).unwrap();
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "0b10"));
+    }
+
+    #[test]
+    fn test_identifier() {
+        // Dashes are part of hostname labels
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "my-service.default.svc")).unwrap();
+        assert_eq!(rest.as_str(), ".default.svc");
+        assert!(matches!(token, Token::Identifier(ref id, Some(ref s)) if id == "my-service" && s.as_str() == "my-service"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "xn--bcher-kva ")).unwrap();
+        assert_eq!(rest.as_str(), " ");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "xn--bcher-kva"));
+
+        // A trailing dash is not
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "my-service-")).unwrap();
+        assert_eq!(rest.as_str(), "-");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "my-service"));
+
+        // Neither is the start of an arrow
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "my-service->example.com")).unwrap();
+        assert_eq!(rest.as_str(), "->example.com");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "my-service"));
+
+        // Words that start with or only have digits are joined too (which includes port ranges)
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "123-abc.nl")).unwrap();
+        assert_eq!(rest.as_str(), ".nl");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "123-abc"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "1-2-3-4.sslip.io")).unwrap();
+        assert_eq!(rest.as_str(), ".sslip.io");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "1-2-3-4"));
+        let (rest, token): (Input, Token) = scan::<nom::error::Error<Input>>(Input::new("<test>", "8000-8099")).unwrap();
+        assert_eq!(rest.as_str(), "");
+        assert!(matches!(token, Token::Identifier(ref id, _) if id == "8000-8099"));
+    }
 }
 
 
@@ -101,7 +243,7 @@ mod tests {
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an action.
-fn scan_action<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_action<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         seq::tuple((bc::tag("!"), comb::cut(cc::alphanumeric1))),
         |(l, name): (Input, Input)| {
@@ -121,7 +263,7 @@ fn scan_action<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IR
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an identifier.
-fn scan_protocol<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_protocol<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         seq::tuple((
             cc::alphanumeric1,
@@ -133,8 +275,27 @@ fn scan_protocol<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) ->
     )(input)
 }
 
+/// Scans a single word of an identifier, i.e., a sequence of alphanumerics, underscores and percentages (the latter for URL-escapes in paths).
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The parts that make up the word.
+/// 
+/// # Errors
+/// This function may error if nom failed to scan a word.
+fn scan_word<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Vec<Input<'a>>, E> {
+    multi::many1(branch::alt((
+        cc::alphanumeric1,
+        bc::is_a("_%"),
+    )))(input)
+}
+
 /// Scans a path identifier (i.e., a word).
 /// 
+/// Words may be joined by dashes (e.g., `my-service` or `1-2-3-4`), as long as the dashes are followed by another word. This way, the arrow in `a->b` and a trailing dash are left alone. Note that this also joins port ranges (e.g., `8000-8099`), which the parser splits again.
+/// 
 /// # Arguments
 /// - `input`: The Input to scan.
 /// 
@@ -143,13 +304,16 @@ fn scan_protocol<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) ->
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an identifier.
-fn scan_identifier<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_identifier<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
-        multi::many1(branch::alt((
-            cc::alphanumeric1,
-            bc::is_a("_%"),
-        ))),
-        |ident: Vec<Input>| {
+        seq::pair(
+            scan_word,
+            multi::many0(seq::pair(multi::many1(bc::tag("-")), scan_word)),
+        ),
+        |(first, rest): DashedWords| {
+            // Flatten the words and the dashes in between them
+            let ident: Vec<Input> = first.into_iter().chain(rest.into_iter().flat_map(|(dashes, word)| dashes.into_iter().chain(word))).collect();
+
             // Merge all of the matched sections together, taking note of the range
             let mut text   : String            = if !ident.is_empty() { String::from(ident[0].as_str()) } else { String::new() };
             let mut source : Option<SourceRef> = if !ident.is_empty() { Some(ident[0]) } else { None };
@@ -174,7 +338,7 @@ fn scan_identifier<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -
 /// 
 /// # Errors
 /// This function may error if nom failed to scan a port number.
-fn scan_port<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_port<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         seq::terminated(
             cc::digit1,
@@ -187,6 +351,74 @@ fn scan_port<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
     )(input)
 }
 
+/// Scans a literal IPv4 address (i.e., four dot-separated octets).
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The parsed `Token`.
+/// 
+/// # Errors
+/// This function may error if nom failed to scan an IP address. Note that octets larger than `255` are not considered part of an IP address, so `999.1.1.1` is left for the other scanners. The same goes for addresses that continue as a hostname (e.g., `10.0.0.1.nip.io`).
+fn scan_ipaddress<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
+    /// Scans a single octet of the IP address.
+    fn scan_octet<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Input<'a>, E> {
+        comb::verify(
+            cc::digit1,
+            |digits: &Input| digits.size() <= 3 && u8::from_str(digits.as_str()).is_ok(),
+        )(input)
+    }
+
+    comb::map(
+        seq::terminated(
+            seq::tuple((
+                scan_octet,
+                seq::preceded(bc::tag("."), scan_octet),
+                seq::preceded(bc::tag("."), scan_octet),
+                seq::preceded(bc::tag("."), scan_octet),
+            )),
+            comb::not(branch::alt((
+                cc::alphanumeric1,
+                bc::is_a("_%"),
+                seq::preceded(bc::is_a(".-"), cc::alphanumeric1),
+            ))),
+        ),
+        |(o1, o2, o3, o4): (Input, Input, Input, Input)| {
+            // Return that as a token
+            Token::IpAddress(format!("{}.{}.{}.{}", o1.as_str(), o2.as_str(), o3.as_str(), o4.as_str()), Some(o1 + o4))
+        },
+    )(input)
+}
+
+/// Scans a literal IPv6 address in brackets (e.g., `[::1]`), as it appears in URLs.
+/// 
+/// Note that this is not part of `scan()`, since it has to go before the punctuation (see `scanner::scan_token()`).
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The parsed `Token`, which keeps the brackets.
+/// 
+/// # Errors
+/// This function may error if nom failed to scan an IP address. Anything in brackets that is not a valid IPv6 address is left for the other scanners, so lists like `[ 80 ]` are unaffected.
+pub(crate) fn scan_ipv6address<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
+    comb::map(
+        comb::verify(
+            seq::tuple((
+                bc::tag("["),
+                bc::is_a("0123456789abcdefABCDEF:."),
+                bc::tag("]"),
+            )),
+            |(_, ip, _): &(Input, Input, Input)| Ipv6Addr::from_str(ip.as_str()).is_ok(),
+        ),
+        |(lbracket, ip, rbracket): (Input, Input, Input)| {
+            Token::IpAddress(format!("[{}]", ip.as_str()), Some(lbracket + rbracket))
+        },
+    )(input)
+}
+
 /// Scans an aterisk, possibly named.
 /// 
 /// # Arguments
@@ -197,7 +429,7 @@ fn scan_port<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an aterisk.
-fn scan_aterisk<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_aterisk<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         seq::pair(
             bc::tag("*"),
@@ -210,10 +442,10 @@ fn scan_aterisk<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> I
                 None    => None,
             };
 
-            // Construct the range
+            // Construct the range (if the name would go out-of-bounds, enlarging leaves it at just the aterisk)
             let mut source: SourceRef = aterisk;
             if tag.is_some() {
-                source.enlarge(1);
+                source.enlarge(1).ok();
             }
 
             // Construct a token out of those
@@ -224,8 +456,47 @@ fn scan_aterisk<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> I
 
 
 
+/// Resolves the escape characters in the (already scanned) contents of a string literal.
+/// 
+/// # Arguments
+/// - `text`: The raw text in between the quotes of the string literal.
+/// 
+/// # Returns
+/// The text with its escapes (`\n`, `\r`, `\t`, `\xNN`, `\u{N...}` and escaped literal characters) resolved.
+/// 
+/// # Panics
+/// This function panics if a hexadecimal escape is not valid. This should never happen, since `scan_string` already verified them.
+fn resolve_escapes(text: &str) -> String {
+    let mut value : String              = String::with_capacity(text.len());
+    let mut chars : std::str::Chars<'_> = text.chars();
+    while let Some(c) = chars.next() {
+        // Non-escaped characters are pushed as-is
+        if c != '\\' { value.push(c); continue; }
+
+        // Otherwise, match on what is escaped
+        match chars.next() {
+            Some('n') => { value.push('\n'); },
+            Some('r') => { value.push('\r'); },
+            Some('t') => { value.push('\t'); },
+            Some('x') => {
+                let code: String = chars.by_ref().take(2).collect();
+                value.push(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32).unwrap_or_else(|| panic!("Got invalid '\\x{}' escape even though scan_string verified it", code)));
+            },
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                value.push(u32::from_str_radix(&code, 16).ok().and_then(char::from_u32).unwrap_or_else(|| panic!("Got invalid '\\u{{{}}}' escape even though scan_string verified it", code)));
+            },
+            Some(c) => { value.push(c); },
+            None    => {},
+        }
+    }
+    value
+}
+
 /// Scans a string literal.
 /// 
+/// Supported escapes are `\n`, `\r`, `\t`, `\\`, `\'` and `\"`, together with `\xNN` (two hex digits) and `\u{N...}` (one to six hex digits) to write arbitrary characters.
+/// 
 /// # Arguments
 /// - `input`: The Input to scan.
 /// 
@@ -233,8 +504,16 @@ fn scan_aterisk<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> I
 /// The parsed `Token`.
 /// 
 /// # Errors
-/// This function may error if nom failed to scan a string.
-fn scan_string<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+/// This function may error if nom failed to scan a string, which includes if it contains an unknown escape or one that does not encode a valid unicode code point (e.g., `\u{110000}`).
+fn scan_string<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
+    /// Scans the given number of hexadecimal digits that together encode a valid unicode code point.
+    fn scan_code_point<'a, E: nom::error::ParseError<Input<'a>>>(min: usize, max: usize) -> impl FnMut(Input<'a>) -> IResult<Input<'a>, Input<'a>, E> {
+        comb::verify(
+            bc::take_while_m_n(min, max, |c: char| c.is_ascii_hexdigit()),
+            |code: &Input<'a>| u32::from_str_radix(code.as_str(), 16).ok().and_then(char::from_u32).is_some(),
+        )
+    }
+
     comb::map(
         seq::tuple((
             bc::tag("\""),
@@ -244,34 +523,46 @@ fn scan_string<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IR
                     bc::take(1usize),
                 ),
                 '\\',
-                cc::one_of("\\'\"ntr"),
+                branch::alt((
+                    comb::value((), cc::one_of("\\'\"ntr")),
+                    comb::value((), seq::pair(bc::tag("x"), scan_code_point(2, 2))),
+                    comb::value((), seq::tuple((bc::tag("u{"), scan_code_point(1, 6), bc::tag("}")))),
+                )),
             ),
             bc::tag("\""),
         )),
         |(l, text, r): (Input, Input, Input)| {
-            // Resolve the text
-            let mut value: String = String::with_capacity(text.size());
-            let mut escaped: bool = false;
-            for c in text.as_str().chars() {
-                if escaped && c == 'n' {
-                    value.push('\n');
-                    escaped = false;
-                } else if escaped && c == 'r' {
-                    value.push('\r');
-                    escaped = false;
-                } else if escaped && c == 't' {
-                    value.push('\t');
-                    escaped = false;
-                } else if !escaped && c == '\\' {
-                    escaped = true;
-                } else {
-                    value.push(c);
-                    escaped = false;
-                }
-            }
+            // Construct a token out of the resolved text
+            Token::String(resolve_escapes(text.as_str()), Some(l + r))
+        }
+    )(input)
+}
 
-            // Construct a token out of those
-            Token::String(value, Some(l + r))
+/// Scans an (unsigned) integer literal in hexadecimal (`0x`), octal (`0o`) or binary (`0b`) notation.
+/// 
+/// The literal is kept as-is (including its prefix), so the parser has to interpret the radix. Literals that run on into something else (e.g., `0b10.nl`) are left to the other scanners.
+/// 
+/// # Arguments
+/// - `input`: The Input to scan.
+/// 
+/// # Returns
+/// The parsed `Token`.
+/// 
+/// # Errors
+/// This function may error if nom failed to scan a prefixed integer.
+fn scan_radix_uint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
+    comb::map(
+        seq::terminated(
+            branch::alt((
+                seq::pair(bc::tag_no_case("0x"), cc::hex_digit1),
+                seq::pair(bc::tag_no_case("0o"), cc::oct_digit1),
+                seq::pair(bc::tag_no_case("0b"), bc::is_a("01")),
+            )),
+            comb::not(branch::alt((cc::alphanumeric1, bc::is_a("_%-.")))),
+        ),
+        |(prefix, digits): (Input, Input)| {
+            let source: SourceRef = prefix + digits;
+            Token::UInt(source.as_str().into(), Some(source))
         }
     )(input)
 }
@@ -286,7 +577,7 @@ fn scan_string<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IR
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an integer.
-fn scan_uint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_uint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         cc::digit1,
         |digits: Input| {
@@ -305,11 +596,11 @@ fn scan_uint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
 /// 
 /// # Errors
 /// This function may error if nom failed to scan an integer.
-fn scan_sint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_sint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         seq::tuple((multi::many1(bc::tag("-")), cc::digit1)),
         |(signs, digits): (Vec<Input>, Input)| {
-            Token::UInt(format!("{}{}", signs.iter().map(|s| s.as_str()).collect::<String>(), digits.as_str()), Some(signs[0] + digits))
+            Token::SInt(format!("{}{}", signs.iter().map(|s| s.as_str()).collect::<String>(), digits.as_str()), Some(signs[0] + digits))
         }
     )(input)
 }
@@ -324,7 +615,7 @@ fn scan_sint<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
 /// 
 /// # Errors
 /// This function may error if nom failed to scan a boolean.
-fn scan_bool<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+fn scan_bool<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     comb::map(
         branch::alt((
             bc::tag("true"),
@@ -341,6 +632,28 @@ fn scan_bool<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
 
 
 /***** LIBRARY *****/
+/// Checks whether the given input starts with a string literal that is never closed.
+/// 
+/// This is used to give a better error message if scanning failed.
+/// 
+/// # Arguments
+/// - `input`: The Input to check.
+/// 
+/// # Returns
+/// True if the input starts with a quote but ends before the matching (unescaped) closing quote is found, or false otherwise.
+pub fn is_unterminated_string(input: Input) -> bool {
+    let mut chars: std::str::Chars<'_> = input.as_str().chars();
+    if chars.next() != Some('"') { return false; }
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => { chars.next(); },
+            '"'  => { return false; },
+            _    => {},
+        }
+    }
+    true
+}
+
 /// Scans one of the possible value tokens.
 /// 
 /// # Arguments
@@ -351,9 +664,11 @@ fn scan_bool<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IRes
 /// 
 /// # Errors
 /// This function may error if nom failed to scan a value token.
-pub fn scan<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token, E> {
+pub fn scan<'a, E: nom::error::ParseError<Input<'a>>>(input: Input<'a>) -> IResult<Input<'a>, Token<'a>, E> {
     branch::alt((
         scan_action,
+        scan_ipaddress,
+        scan_radix_uint,
         scan_port,
         scan_protocol,
         scan_identifier,
@@ -4,7 +4,7 @@
 //  Created:
 //    17 Oct 2022, 19:29:02
 //  Last edited:
-//    04 Nov 2022, 08:18:42
+//    16 Oct 2026, 18:57:00
 //  Auto updated?
 //    Yes
 // 
@@ -22,6 +22,9 @@ use std::ops::{Add, AddAssign, RangeFrom};
 use console::{style, Style};
 use nom::CompareResult;
 
+use crate::errors::OutOfBoundsError;
+use crate::spec::{TextPos, TextRange};
+
 
 /***** HELPER MACROS *****/
 /// Generates a string of the given number of spaces.
@@ -37,6 +40,7 @@ macro_rules! spaces {
 /***** TESTS *****/
 #[cfg(test)]
 mod tests {
+    use crate::errors::PrettyError as _;
     use crate::tests::assert_scan;
     use super::*;
 
@@ -45,6 +49,156 @@ mod tests {
         // Create some random source
         assert_scan!(nom::combinator::value((), nom::bytes::complete::tag::<&str, SourceRef, nom::error::VerboseError<SourceRef>>("//")), "// Hello there!", 2);
     }
+
+    #[test]
+    fn test_display() {
+        console::set_colors_enabled(false);
+        let source: &str = "foo -> bar\nhello there,\nwow -> nice,\n";
+
+        // Display a single-line range
+        let range: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", source, 7, 3) };
+        assert_eq!(format!("{}", range.display(Style::new())), " --> <test>:1:8\n  |\n1 | foo -> bar\n  |        ^^^\n");
+
+        // Display a range that spans three lines
+        let range: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", source, 7, 20) };
+        assert_eq!(format!("{}", range.display(Style::new())), concat!(
+            " --> <test>:1:8\n",
+            "  |\n",
+            "1 | foo -> bar\n",
+            "  |        ^^^\n",
+            "2 | hello there,\n",
+            "  | ^^^^^^^^^^^^\n",
+            "3 | wow -> nice,\n",
+            "  | ^^^\n",
+        ));
+
+        // The owned version should display the same
+        let text: SourceText = range.into();
+        assert_eq!(format!("{}", text.display(Style::new())), format!("{}", range.display(Style::new())));
+    }
+
+    #[test]
+    fn test_tab_width() {
+        console::set_colors_enabled(false);
+        let source: &str = "[rules]\n\tfoo -> bar,\n\t\tx\t-> y,\n";
+
+        // By default, a tab is a single column
+        let range: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", source, 9, 3) };
+        assert_eq!(range.range(), TextRange::new(TextPos::new(2, 2), TextPos::new(2, 4)));
+        assert_eq!(format!("{}", range.display(Style::new())), " --> <test>:2:2\n  |\n2 | \tfoo -> bar,\n  |  ^^^\n");
+
+        // But it may advance to the next tab stop instead
+        assert_eq!(range.range_with_tab_width(4), TextRange::new(TextPos::new(2, 5), TextPos::new(2, 7)));
+        assert_eq!(range.range_with_tab_width(8), TextRange::new(TextPos::new(2, 9), TextPos::new(2, 11)));
+        assert_eq!(format!("{}", range.display(Style::new()).with_tab_width(4)), " --> <test>:2:5\n  |\n2 |     foo -> bar,\n  |     ^^^\n");
+
+        // Tabs after other characters only advance to the next stop
+        let range: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", source, 25, 2) };
+        assert_eq!(range.as_str(), "->");
+        assert_eq!(range.range_with_tab_width(4), TextRange::new(TextPos::new(3, 13), TextPos::new(3, 14)));
+        assert_eq!(format!("{}", range.display(Style::new()).with_tab_width(4)), " --> <test>:3:13\n  |\n3 |         x   -> y,\n  |             ^^\n");
+    }
+
+    #[test]
+    fn test_enlarge() {
+        // Enlarge within bounds
+        let mut source: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", "hello", 1, 2) };
+        assert_eq!(source.enlarge(2), Ok(()));
+        assert_eq!(source.as_str(), "ello");
+
+        // Enlarging beyond the end fails and leaves it untouched
+        assert_eq!(source.enlarge(1), Err(OutOfBoundsError{ offset: 1, size: 4, n: 1, len: 5 }));
+        assert_eq!(source.as_str(), "ello");
+        let mut source: SourceRef = SourceRef::new("<test>", "");
+        assert!(source.enlarge(1).is_err());
+    }
+
+    #[test]
+    fn test_from_range() {
+        let source: &str = "foo -> bar\nhéllo there,\nwow -> nice,\n";
+
+        // Ranges map back onto the same characters
+        let range: SourceRef = SourceRef::from_range("<test>", source, TextRange::new(TextPos::new(1, 8), TextPos::new(1, 10))).unwrap();
+        assert_eq!(range.as_str(), "bar");
+        let range: SourceRef = SourceRef::from_range("<test>", source, TextRange::new(TextPos::new(2, 2), TextPos::new(3, 3))).unwrap();
+        assert_eq!(range.as_str(), "éllo there,\nwow");
+        assert_eq!(range.range(), TextRange::new(TextPos::new(2, 2), TextPos::new(3, 3)));
+
+        // Ranges that are absent, reversed or out-of-bounds have no reference
+        assert!(SourceRef::from_range("<test>", source, TextRange::None).is_none());
+        assert!(SourceRef::from_range("<test>", source, TextRange::new(TextPos::new(1, 3), TextPos::new(1, 1))).is_none());
+        assert!(SourceRef::from_range("<test>", source, TextRange::new(TextPos::new(4, 1), TextPos::new(4, 2))).is_none());
+    }
+
+    #[test]
+    fn test_slice_index() {
+        use nom::InputIter as _;
+
+        // ASCII input should map characters one-to-one to bytes
+        let source: SourceRef = SourceRef::new("<test>", "hello");
+        assert_eq!(source.slice_index(0), Ok(0));
+        assert_eq!(source.slice_index(3), Ok(3));
+        assert_eq!(source.slice_index(5), Ok(5));
+        assert_eq!(source.slice_index(7), Err(nom::Needed::new(2)));
+
+        // Multi-byte input should return the byte offset of the character
+        let source: SourceRef = SourceRef::new("<test>", "h\u{e9}llo \u{1f980}!");
+        assert_eq!(source.slice_index(1), Ok(1));
+        assert_eq!(source.slice_index(2), Ok(3));
+        assert_eq!(source.slice_index(6), Ok(7));
+        assert_eq!(source.slice_index(7), Ok(11));
+        assert_eq!(source.slice_index(8), Ok(12));
+        assert_eq!(source.slice_index(9), Err(nom::Needed::new(1)));
+
+        // It should also respect the offset of the SourceRef
+        let source: SourceRef = unsafe{ SourceRef::new_with_raw_offset("<test>", "\u{e9}\u{e9}abc", 2, 4) };
+        assert_eq!(source.slice_index(1), Ok(2));
+        assert_eq!(source.slice_index(3), Ok(4));
+    }
+
+    #[test]
+    #[ignore = "only run as a subprocess of `test_no_stdout`"]
+    fn test_no_stdout_child() {
+        // Scan something and render an error for it
+        let tokens = crate::tests::scan_tokens("[rules]\n*1.example.com -> !forward,\n");
+        let err: crate::errors::ParseError = crate::parser::parse(&tokens).unwrap_err();
+        let _ = format!("{}", err.prettyprint());
+        let _ = format!("{}", tokens[1].source().unwrap().display(Style::new()));
+    }
+
+    #[test]
+    fn test_no_stdout() {
+        // Run the test above in a separate process so that we can see what it writes to stdout
+        let output: std::process::Output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args([ "--ignored", "--exact", "source::tests::test_no_stdout_child", "--nocapture", "--test-threads=1" ])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        // Everything that is not the test harness' own output is unexpected
+        for line in String::from_utf8_lossy(&output.stdout).lines() {
+            assert!(line.is_empty() || line.starts_with("running ") || (line.starts_with("test ") && line.ends_with(" ... ok")) || line.starts_with("test result: "), "Unexpected output on stdout: '{}'", line);
+        }
+    }
+}
+
+
+
+
+
+/***** HELPER FUNCTIONS *****/
+/// Computes the width of the given character when it is displayed at the given column.
+/// 
+/// # Arguments
+/// - `c`: The character to display.
+/// - `col`: The (1-indexed) column at which it is displayed.
+/// - `tab_width`: The distance between tab stops. If this is 1 (or 0), tabs are a single column wide like any other character.
+/// 
+/// # Returns
+/// The number of columns that the character takes up.
+#[inline]
+fn char_width(c: char, col: usize, tab_width: usize) -> usize {
+    if c == '\t' && tab_width > 1 { tab_width - (col - 1) % tab_width } else { 1 }
 }
 
 
@@ -55,9 +209,28 @@ mod tests {
 /// Auxillary struct that can write a SourceRef or SourceText to the given writer.
 pub struct SourceTextDisplay<'a, T> {
     /// The thing to display.
-    source : &'a T,
+    source    : &'a T,
     /// The style (general colour) to display it with.
-    style  : Style,
+    style     : Style,
+    /// The distance between tab stops, which tabs are expanded to (1 to write them as-is).
+    tab_width : usize,
+}
+
+impl<'a, T> SourceTextDisplay<'a, T> {
+    /// Expands tabs to the given tab stops, such that the markers line up with the source as it is shown in an editor.
+    /// 
+    /// This also changes the column shown in the header to the displayed one. By default, tabs are written as-is and count as a single column.
+    /// 
+    /// # Arguments
+    /// - `tab_width`: The distance between tab stops (e.g., 4 or 8).
+    /// 
+    /// # Returns
+    /// The same SourceTextDisplay, for chaining.
+    #[inline]
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
 }
 
 impl<'a, T> Display for SourceTextDisplay<'a, T>
@@ -75,18 +248,23 @@ where
 
             // Switch on multi-line mode or not
             if start.0 == end.0 {
-                i == start.0 && j >= start.1 - 1 && j <= end.1 - 1
+                i == start.0 && j >= start.1 - 1 && j < end.1
             } else {
-                (i == start.0 && j >= start.1 - 1) || (i > start.0 && i < end.0) || (i == end.0 && j <= end.1 - 1)
+                (i == start.0 && j >= start.1 - 1) || (i > start.0 && i < end.0) || (i == end.0 && j < end.1)
             }
         };
 
         // Compute the maximum line length
         let max_line_len: usize = ((source.end().0 as f32).log10() + 1.0).floor() as usize;
 
+        // Find the displayed column of the start (which differs from the character column if tabs are expanded)
+        let mut start_col: usize = 1;
+        if let Some((_, l)) = source.lines().next() {
+            for c in l.chars().take(source.start().1 - 1) { start_col += char_width(c, start_col, self.tab_width); }
+        }
+
         // Write the file thingy + a "whitespace"
-        println!("{:?}", source);
-        writeln!(f, "{}{} {}:{}:{}", spaces!(max_line_len), style("-->").bright().blue(), source.name(), source.start().0, source.start().1)?;
+        writeln!(f, "{}{} {}:{}:{}", spaces!(max_line_len), style("-->").bright().blue(), source.name(), source.start().0, start_col)?;
         writeln!(f, "{} {}", spaces!(max_line_len), style("|").bright().blue())?;
 
         // Write the lines that are marked
@@ -95,29 +273,36 @@ where
             let sline: String = format!("{}", i);
             write!(f, "{}{} {} ", spaces!(max_line_len - sline.len()), sline, style("|").bright().blue())?;
     
-            // Start writing the line itself, highlighing what is necessary
-            for (j, c) in l.char_indices() {
-                
+            // Start writing the line itself, highlighing what is necessary (note that we count in characters, not bytes, to match the column numbers)
+            let mut col: usize = 1;
+            for (j, c) in l.chars().enumerate() {
+                let width: usize = char_width(c, col, self.tab_width);
+                let text: String = if c == '\t' && self.tab_width > 1 { spaces!(width) } else { c.to_string() };
                 if is_in_range(i, j) {
-                    write!(f, "{}", self.style.apply_to(c))?;
+                    write!(f, "{}", self.style.apply_to(text))?;
                 } else {
-                    write!(f, "{}", c)?;
+                    write!(f, "{}", text)?;
                 }
+                col += width;
             }
 
             // Write the end-of-line
             writeln!(f)?;
 
-            // Now go in again, applying the marker thingies
+            // Now go in again, applying the marker thingies (up to the last marked character, to avoid trailing whitespace)
+            let n_chars: usize = l.chars().enumerate().filter(|(j, _)| is_in_range(i, *j)).map(|(j, _)| j + 1).last().unwrap_or(0);
 
             // Write the start of the line
             write!(f, "{} {} ", spaces!(max_line_len), style("|").bright().blue())?;
-            for (j, _) in l.char_indices() {
+            let mut col: usize = 1;
+            for (j, c) in l.chars().enumerate().take(n_chars) {
+                let width: usize = char_width(c, col, self.tab_width);
                 if is_in_range(i, j) {
-                    write!(f, "{}", self.style.apply_to('^'))?;
+                    write!(f, "{}", self.style.apply_to((0..width).map(|_| '^').collect::<String>()))?;
                 } else {
-                    write!(f, " ")?;
+                    write!(f, "{}", spaces!(width))?;
                 }
+                col += width;
             }
             writeln!(f)?;
         }
@@ -173,6 +358,9 @@ impl<'a> SourceRef<'a> {
     /// 
     /// Be careful they are in the range of the given source!
     /// 
+    /// # Safety
+    /// The caller must ensure that `offset` and `size` describe a range within `source` that starts and ends on a character boundary.
+    /// 
     /// # Arguments
     /// - `name`: The (file)name of the source text. Should basically be some way for the user to identify the origin of the source text.
     /// - `source`: The actual source text itself.
@@ -192,15 +380,66 @@ impl<'a> SourceRef<'a> {
         }
     }
 
+    /// Constructor for the SourceRef that refers to the given range in the source text.
+    /// 
+    /// This is the inverse of `SourceRef::range()`, i.e., every character counts as a single column.
+    /// 
+    /// # Arguments
+    /// - `name`: The (file)name of the source text. Should basically be some way for the user to identify the origin of the source text.
+    /// - `source`: The actual source text itself.
+    /// - `range`: The range in the source text to refer to.
+    /// 
+    /// # Returns
+    /// A new SourceRef instance, or `None` if the range is `TextRange::None` or does not lie within the source text.
+    pub fn from_range(name: &'a str, source: &'a str, range: TextRange) -> Option<Self> {
+        let (start, end): (TextPos, TextPos) = match range {
+            TextRange::Some(start, end) => (start, end),
+            TextRange::None             => { return None; },
+        };
+
+        // Walk the source up to the end position to find the offsets of both positions
+        let mut line_i : usize         = 1;
+        let mut col_i  : usize         = 1;
+        let mut offset : Option<usize> = None;
+        for (i, c) in source.char_indices() {
+            if line_i == start.line && col_i == start.col { offset = Some(i); }
+            if line_i == end.line && col_i == end.col {
+                let offset: usize = offset?;
+                return Some(Self {
+                    source,
+                    offset,
+                    size : i + c.len_utf8() - offset,
+
+                    name,
+                });
+            }
+
+            // Move to the next position
+            if c == '\n' {
+                line_i += 1;
+                col_i   = 1;
+            } else {
+                col_i += 1;
+            }
+        }
+
+        // We never reached the end
+        None
+    }
+
 
 
-    /// Gros the SourceRef by the given amount to the right.
+    /// Grows the SourceRef by the given amount (in bytes) to the right.
     /// 
-    /// # Panics
-    /// This function panics if this causes the SourceRef to go out-of-bounds.
-    pub fn enlarge(&mut self, n: usize) {
-        if self.offset + self.size + n > self.source.len() { panic!("Enlarging a SourceRef with offset {} and {} characters (ending at {}) with {} characters overflows for a source text of {} characters", self.offset, self.size, self.offset + self.size - 1, n, self.source.len()); }
+    /// # Arguments
+    /// - `n`: The number of bytes to grow with.
+    /// 
+    /// # Errors
+    /// This function errors if this causes the SourceRef to go out-of-bounds. In that case, the SourceRef is left untouched.
+    pub fn enlarge(&mut self, n: usize) -> Result<(), OutOfBoundsError> {
+        if self.offset + self.size + n > self.source.len() { return Err(OutOfBoundsError{ offset: self.offset, size: self.size, n, len: self.source.len() }); }
         self.size += n;
+        Ok(())
     }
 
 
@@ -263,7 +502,7 @@ impl<'a> SourceRef<'a> {
             // A newline (or end-of-file) is where it all happens
             if c == '\n' || iter.peek().is_none() {
                 // If we have been within the offset range, store it
-                if self.offset <= i && self.offset + self.size - 1 >= line_start {
+                if self.offset <= i && self.offset + self.size > line_start {
                     if source_start.is_none() { source_start = Some(line_start); }
                     source = Some(&self.source[*source_start.as_ref().unwrap()..i + 1]);
                 }
@@ -301,6 +540,51 @@ impl<'a> SourceRef<'a> {
         }
     }
 
+    /// Computes the range of this reference in terms of lines and columns in the source text.
+    /// 
+    /// Every character counts as a single column, including tabs (see `range_with_tab_width()` to change that).
+    /// 
+    /// # Returns
+    /// A new TextRange that spans this reference, or `TextRange::None` if it is empty.
+    #[inline]
+    pub fn range(&self) -> TextRange { self.range_with_tab_width(1) }
+
+    /// Computes the range of this reference in terms of lines and columns in the source text, where tabs advance to the next tab stop.
+    /// 
+    /// # Arguments
+    /// - `tab_width`: The distance between tab stops (e.g., 4 or 8). If this is 1, tabs count as a single column.
+    /// 
+    /// # Returns
+    /// A new TextRange that spans this reference, or `TextRange::None` if it is empty.
+    pub fn range_with_tab_width(&self, tab_width: usize) -> TextRange {
+        // EZ early quit if we're empty
+        if self.size == 0 { return TextRange::None; }
+
+        // Walk the source up to the end of the reference to find the start and end positions
+        let mut line_i : usize           = 1;
+        let mut col_i  : usize           = 1;
+        let mut start  : Option<TextPos> = None;
+        let mut end    : Option<TextPos> = None;
+        for (i, c) in self.source.char_indices() {
+            if i >= self.offset + self.size { break; }
+
+            // Mark start and/or end positions
+            if i == self.offset { start = Some(TextPos::new(line_i, col_i)); }
+            if i >= self.offset { end = Some(TextPos::new(line_i, col_i)); }
+
+            // Move to the next position
+            if c == '\n' {
+                line_i += 1;
+                col_i   = 1;
+            } else {
+                col_i += char_width(c, col_i, tab_width);
+            }
+        }
+
+        // Done
+        TextRange::new(start, end)
+    }
+
     /// Returns the internal source range for this reference.
     /// 
     /// # Panics
@@ -316,10 +600,11 @@ impl<'a> SourceRef<'a> {
     /// # Returns
     /// A new SourceTextDisplay instance that implements `Display`.
     #[inline]
-    pub fn display<'b, 'c>(&'b self, style: Style) -> SourceTextDisplay<'b, Self> {
+    pub fn display<'b>(&'b self, style: Style) -> SourceTextDisplay<'b, Self> {
         SourceTextDisplay {
-            source : self,
+            source    : self,
             style,
+            tab_width : 1,
         }
     }
 }
@@ -327,7 +612,7 @@ impl<'a> SourceRef<'a> {
 impl<'a> PartialEq for SourceRef<'a> {
     fn eq(&self, other: &Self) -> bool {
         // Only compare the ranges & source text being the same
-        (self.source as *const str) == (other.source as *const str) && self.offset == other.offset && self.size == other.size
+        std::ptr::eq(self.source, other.source) && self.offset == other.offset && self.size == other.size
     }
 }
 
@@ -344,7 +629,7 @@ impl<'a> Add for &SourceRef<'a> {
 
     fn add(self, rhs: Self) -> Self::Output {
         // Simply create a new SourceRef that spans both
-        if (self.source as *const str) != (rhs.source as *const str) { panic!("Cannot add two SourceRef's with difference source tests ({} VS {})", self.name, rhs.name); }
+        if !std::ptr::eq(self.source, rhs.source) { panic!("Cannot add two SourceRef's with difference source tests ({} VS {})", self.name, rhs.name); }
         SourceRef {
             source : self.source,
             offset : self.offset,
@@ -357,7 +642,7 @@ impl<'a> Add for &SourceRef<'a> {
 impl<'a> AddAssign for SourceRef<'a> {
     fn add_assign(&mut self, rhs: Self) {
         // Simply create a new SourceRef that spans both
-        if (self.source as *const str) != (rhs.source as *const str) { panic!("Cannot add two SourceRef's with difference source tests ({} VS {})", self.name, rhs.name); }
+        if !std::ptr::eq(self.source, rhs.source) { panic!("Cannot add two SourceRef's with difference source tests ({} VS {})", self.name, rhs.name); }
         self.size = (rhs.offset + rhs.size) - self.offset;
     }
 }
@@ -417,12 +702,16 @@ impl<'a> nom::InputIter for SourceRef<'a> {
         self.source[self.offset..self.offset + self.size].char_indices().find_map(|(i, c)| if predicate(c) { Some(i) } else { None })
     }
     fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
-        let mut count: usize = count;
-        while let Some((i, _)) = self.source[self.offset..self.offset + self.size].char_indices().next() {
-            count -= 1;
-            if count == 0 { return Ok(i); }
+        // Walk the characters once, returning the byte offset of the `count`th one
+        let mut n_chars: usize = 0;
+        for (i, _) in self.as_str().char_indices() {
+            if n_chars == count { return Ok(i); }
+            n_chars += 1;
         }
-        Err(nom::Needed::new(count))
+
+        // If we've precisely reached the end, that's fine too; otherwise, we need more input
+        if n_chars == count { return Ok(self.size); }
+        Err(nom::Needed::new(count - n_chars))
     }
 }
 impl<'a> nom::UnspecializedInput for SourceRef<'a> {}
@@ -446,10 +735,9 @@ impl<'a> nom::Compare<&str> for SourceRef<'a> {
 }
 impl<'a> nom::Slice<RangeFrom<usize>> for SourceRef<'a> {
     fn slice(&self, range: RangeFrom<usize>) -> Self {
-        if range.start >= self.size { panic!("Cannot `slice()` {} characters of a SourceRef of size {}", range.start, self.size); }
-        println!("Slicing '{}' -> '{}'", &self.source[self.offset..self.offset + self.size], &self.source[(self.offset + range.start)..(self.offset + range.start) + (self.size - range.start)]);
+        if range.start > self.size { panic!("Cannot `slice()` {} characters of a SourceRef of size {}", range.start, self.size); }
         Self {
-            source : &self.source,
+            source : self.source,
             offset : self.offset + range.start,
             size   : self.size - range.start,
 
@@ -459,11 +747,7 @@ impl<'a> nom::Slice<RangeFrom<usize>> for SourceRef<'a> {
 }
 impl<'a> nom::Offset for SourceRef<'a> {
     fn offset(&self, second: &Self) -> usize {
-        if self.offset >= second.offset {
-            self.offset - second.offset
-        } else {
-            second.offset - self.offset
-        }
+        self.offset.abs_diff(second.offset)
     }
 }
 
@@ -541,6 +825,12 @@ impl SourceText {
     /// A tuple with `(line, column)`.
     #[inline]
     pub fn end(&self) -> (usize, usize) { self.end }
+    /// Returns the start- and end positions as a TextRange.
+    /// 
+    /// # Returns
+    /// A new TextRange that spans this source text, or `TextRange::None` if it is empty.
+    #[inline]
+    pub fn range(&self) -> TextRange { if self.size > 0 { TextRange::new(TextPos::new(self.start.0, self.start.1), TextPos::new(self.end.0, self.end.1)) } else { TextRange::None } }
 
 
 
@@ -561,8 +851,9 @@ impl SourceText {
     #[inline]
     pub fn display<'a>(&'a self, style: Style) -> SourceTextDisplay<'a, Self> {
         SourceTextDisplay {
-            source : self,
+            source    : self,
             style,
+            tab_width : 1,
         }
     }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    07 Oct 2022, 22:12:02
 //  Last edited:
-//    22 Oct 2022, 15:40:46
+//    16 Oct 2026, 18:36:23
 //  Auto updated?
 //    Yes
 // 
@@ -16,32 +16,263 @@
 use std::fmt::Debug;
 
 
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal Node to build ranges from.
+    #[derive(Clone, Debug)]
+    struct Word {
+        range : TextRange,
+    }
+    impl Node for Word {
+        fn range(&self) -> TextRange { self.range }
+    }
+
+    #[test]
+    fn test_text_pos() {
+        // Positions are ordered by line, then column
+        assert!(TextPos::new(1, 1) < TextPos::new(1, 2));
+        assert!(TextPos::new(1, 80) < TextPos::new(2, 1));
+        assert_eq!(TextPos::new(3, 4), TextPos{ line: 3, col: 4 });
+        assert_eq!(TextPos::new(2, 5).max(TextPos::new(2, 3)), TextPos::new(2, 5));
+    }
+
+    #[test]
+    fn test_text_range() {
+        // Construct a range and query it
+        let range: TextRange = TextRange::new(TextPos::new(1, 2), TextPos::new(3, 4));
+        assert_eq!(range, TextRange::Some(TextPos::new(1, 2), TextPos::new(3, 4)));
+        assert_eq!(range.start(), Some(TextPos::new(1, 2)));
+        assert_eq!(range.end(), Some(TextPos::new(3, 4)));
+        assert!(range.is_some());
+        assert!(!range.is_none());
+
+        // Single-character ranges start and end on the same position
+        let range: TextRange = TextRange::new(TextPos::new(5, 6), TextPos::new(5, 6));
+        assert_eq!(range.start(), range.end());
+
+        // A missing position makes the whole range missing
+        assert_eq!(TextRange::new(None, TextPos::new(1, 1)), TextRange::None);
+        assert_eq!(TextRange::new(TextPos::new(1, 1), None), TextRange::None);
+        assert_eq!(TextRange::new(None, None), TextRange::None);
+        assert_eq!(TextRange::None.start(), None);
+        assert_eq!(TextRange::None.end(), None);
+        assert!(TextRange::None.is_none());
+        assert!(!TextRange::None.is_some());
+    }
+
+    #[test]
+    fn test_text_range_join() {
+        let a: TextRange = TextRange::new(TextPos::new(1, 1), TextPos::new(1, 5));
+        let b: TextRange = TextRange::new(TextPos::new(2, 3), TextPos::new(4, 1));
+
+        // Joining spans both, regardless of order
+        assert_eq!(a.join(b), TextRange::new(TextPos::new(1, 1), TextPos::new(4, 1)));
+        assert_eq!(b.join(a), TextRange::new(TextPos::new(1, 1), TextPos::new(4, 1)));
+        assert_eq!(a.join(TextRange::new(TextPos::new(1, 2), TextPos::new(1, 3))), a);
+
+        // Joining with None yields the other
+        assert_eq!(a.join(TextRange::None), a);
+        assert_eq!(TextRange::None.join(b), b);
+        assert_eq!(TextRange::None.join(TextRange::None), TextRange::None);
+
+        // Spanning multiple ranges skips the missing ones
+        assert_eq!(TextRange::spanning([ TextRange::None, b, TextRange::None, a ]), TextRange::new(TextPos::new(1, 1), TextPos::new(4, 1)));
+        assert_eq!(TextRange::spanning([ TextRange::None ]), TextRange::None);
+        assert_eq!(TextRange::spanning(std::iter::empty()), TextRange::None);
+
+        // Ranges can be built from the first and last node
+        let (start, end): (Word, Word) = (Word{ range: a }, Word{ range: b });
+        assert_eq!(TextRange::from((&start, &end)), TextRange::new(TextPos::new(1, 1), TextPos::new(4, 1)));
+        assert_eq!(TextRange::from((&start, &Word{ range: TextRange::None })), TextRange::None);
+    }
+
+    #[test]
+    fn test_node() {
+        use crate::ast::{Endpoint, Rule};
+        use crate::source::{SourceRef, SourceText};
+        use crate::tokens::Token;
+
+        /// Returns the range of any node, proving that it implements the trait.
+        fn range_of(node: &impl Node) -> (TextRange, Option<TextPos>, Option<TextPos>) { (node.range(), node.start(), node.end()) }
+
+        // Tokens, both borrowed and owned, are nodes
+        let source: &str = "foo -> bar,";
+        let token: Token<SourceRef> = Token::Arrow(Some(unsafe{ SourceRef::new_with_raw_offset("<test>", source, 4, 2) }));
+        assert_eq!(range_of(&token), (TextRange::new(TextPos::new(1, 5), TextPos::new(1, 6)), Some(TextPos::new(1, 5)), Some(TextPos::new(1, 6))));
+        let token: Token<SourceText> = token.into();
+        assert_eq!(range_of(&token).0, TextRange::new(TextPos::new(1, 5), TextPos::new(1, 6)));
+        assert_eq!(range_of(&Token::<SourceText>::Comma(None)), (TextRange::None, None, None));
+
+        // So are AST nodes
+        let tokens = crate::tests::scan_tokens(source);
+        let config = crate::parser::parse(&[ vec![ Token::RulesSection(None) ], tokens ].concat()).unwrap();
+        let rule: &Rule = &config.patterns[0].rules[0];
+        assert_eq!(range_of(rule), (TextRange::new(TextPos::new(1, 1), TextPos::new(1, 11)), Some(TextPos::new(1, 1)), Some(TextPos::new(1, 11))));
+        assert_eq!(range_of(&rule.lhs.base).0, TextRange::new(TextPos::new(1, 1), TextPos::new(1, 3)));
+        assert_eq!(range_of(&Endpoint::Wildcard), (TextRange::None, None, None));
+    }
+}
+
+
+
+
+
 /***** LIBRARY *****/
-/// Defines how a node in the AST looks like.
-pub trait Node<T>: Clone + Debug {
-    // /// Returns the entire range of the node in the parent source text.
-    // fn range(&self) -> TextRange;
+/// Defines a position in the source text.
+/// 
+/// Positions are ordered by line first, then by column.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TextPos {
+    /// The line number of the position (one-indexed).
+    pub line : usize,
+    /// The column number of the position (one-indexed).
+    pub col  : usize,
+}
 
-    // /// Returns the start position of this node's source text.
-    // #[inline]
-    // fn start(&self) -> TextPos { self.range().start() }
+impl TextPos {
+    /// Constructor for the TextPos.
+    /// 
+    /// # Arguments
+    /// - `line`: The line number of the position (one-indexed).
+    /// - `col`: The column number of the position (one-indexed).
+    /// 
+    /// # Returns
+    /// A new TextPos instance.
+    #[inline]
+    pub fn new(line: usize, col: usize) -> Self {
+        Self {
+            line,
+            col,
+        }
+    }
+}
 
-    // /// Returns the end position of this node's source text.
-    // #[inline]
-    // fn end(&self) -> TextPos { self.range().end() }
 
 
+/// Defines a range in the source text, which may not be present (e.g., for nodes that are generated instead of parsed).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TextRange {
+    /// There is no range.
+    None,
+    /// There is a range, which spans from the first position up to and including the second position.
+    Some(TextPos, TextPos),
+}
 
-    // Child-overridable
-    /// Returns the reference to the source text that this node is created from. If it does not have such an origin, returns None.
+impl TextRange {
+    /// Constructor for the TextRange that creates it from a start and end position.
+    /// 
+    /// # Arguments
+    /// - `start`: The start position of the range (inclusive). If this is `None`, then the range will be `TextRange::None`.
+    /// - `end`: The end position of the range (inclusive). If this is `None`, then the range will be `TextRange::None`.
+    /// 
+    /// # Returns
+    /// A new TextRange instance.
+    #[inline]
+    pub fn new(start: impl Into<Option<TextPos>>, end: impl Into<Option<TextPos>>) -> Self {
+        match (start.into(), end.into()) {
+            (Some(start), Some(end)) => Self::Some(start, end),
+            _                        => Self::None,
+        }
+    }
+
+    /// Constructor for the TextRange that spans all of the given ranges.
+    /// 
+    /// # Arguments
+    /// - `ranges`: The ranges to span. Any `TextRange::None`s among them are ignored.
     /// 
     /// # Returns
-    /// The SourceRef if this Node had a source.
+    /// A new TextRange from the earliest start to the latest end, or `TextRange::None` if there are no (non-None) ranges.
+    #[inline]
+    pub fn spanning(ranges: impl IntoIterator<Item = TextRange>) -> Self {
+        ranges.into_iter().fold(Self::None, Self::join)
+    }
+
+
+
+    /// Joins this range with another one, such that the result spans both.
     /// 
-    /// # Errors
-    /// This function may error for child-specific reasons.
+    /// # Arguments
+    /// - `other`: The other range to join with. The ranges do not have to overlap or be in order.
     /// 
-    /// # Panics
-    /// This function may panic for child-specific reasons.
-    fn source<'a>(&'a self) -> &'a Option<T>;
+    /// # Returns
+    /// A new TextRange from the earliest start to the latest end. If either range is `TextRange::None`, then the other is returned as-is.
+    pub fn join(self, other: TextRange) -> Self {
+        match (self, other) {
+            (Self::Some(s1, e1), Self::Some(s2, e2)) => Self::Some(s1.min(s2), e1.max(e2)),
+            (Self::None, range) | (range, Self::None) => range,
+        }
+    }
+
+
+
+    /// Returns the start position of this range, if any.
+    #[inline]
+    pub fn start(&self) -> Option<TextPos> { if let Self::Some(start, _) = self { Some(*start) } else { None } }
+
+    /// Returns the end position (inclusive) of this range, if any.
+    #[inline]
+    pub fn end(&self) -> Option<TextPos> { if let Self::Some(_, end) = self { Some(*end) } else { None } }
+
+    /// Returns whether this range is `TextRange::None`.
+    #[inline]
+    pub fn is_none(&self) -> bool { matches!(self, Self::None) }
+
+    /// Returns whether this range is `TextRange::Some`.
+    #[inline]
+    pub fn is_some(&self) -> bool { matches!(self, Self::Some(_, _)) }
+}
+
+
+
+impl<A: Node, B: Node> From<(&A, &B)> for TextRange {
+    /// Creates a range from the start of the first node up to and including the end of the second node.
+    /// 
+    /// If either node has no range, then neither does the result (use `TextRange::join()` to ignore missing ranges instead).
+    #[inline]
+    fn from(value: (&A, &B)) -> Self { Self::new(value.0.start(), value.1.end()) }
+}
+
+
+
+/// Defines how a node in the AST (or a token) looks like.
+/// 
+/// This is the only trait shared by tokens and AST nodes. Tokens additionally have an inherent `Token::source()` to get their exact source text.
+/// 
+/// # Example
+/// ```rust
+/// use proxy_lang::spec::{Node, TextPos, TextRange};
+/// 
+/// #[derive(Clone, Debug)]
+/// struct Word {
+///     range : TextRange,
+/// }
+/// impl Node for Word {
+///     fn range(&self) -> TextRange { self.range }
+/// }
+/// 
+/// let word: Word = Word { range: TextRange::new(TextPos::new(1, 1), TextPos::new(1, 5)) };
+/// assert_eq!(word.start(), Some(TextPos::new(1, 1)));
+/// assert_eq!(word.end(), Some(TextPos::new(1, 5)));
+/// ```
+pub trait Node: Clone + Debug {
+    // Child-overridable
+    /// Returns the entire range of the node in the parent source text.
+    /// 
+    /// # Returns
+    /// The TextRange of this node, or `TextRange::None` if it has no origin in the source text.
+    fn range(&self) -> TextRange;
+
+
+
+    // Global
+    /// Returns the start position of this node's source text, if any.
+    #[inline]
+    fn start(&self) -> Option<TextPos> { self.range().start() }
+
+    /// Returns the end position of this node's source text, if any.
+    #[inline]
+    fn end(&self) -> Option<TextPos> { self.range().end() }
 }
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2022, 22:57:03
 //  Last edited:
-//    04 Nov 2022, 08:20:49
+//    16 Oct 2026, 18:14:32
 //  Auto updated?
 //    Yes
 // 
@@ -16,6 +16,9 @@ use std::io::Read;
 use std::fs::{self, DirEntry, File, ReadDir};
 use std::path::PathBuf;
 
+use crate::source::SourceRef;
+use crate::tokens::Token;
+
 
 /***** CONSTANTS *****/
 /// The path to the test directory.
@@ -77,6 +80,32 @@ pub(crate) use assert_scan;
 
 
 /***** LIBRARY *****/
+/// Scans the given source text to a list of tokens that still reference it (i.e., are not yet converted to owned SourceTexts).
+/// 
+/// # Arguments
+/// - `source`: The source text to scan.
+/// 
+/// # Returns
+/// The scanned tokens.
+/// 
+/// # Panics
+/// This function panics if the scanner failed to scan the source text.
+pub fn scan_tokens(source: &str) -> Vec<Token<SourceRef<'_>>> {
+    let mut input  : SourceRef             = SourceRef::new("<test>", source);
+    let mut tokens : Vec<Token<SourceRef>> = vec![];
+    while !input.is_empty() {
+        match crate::scanner::scanner::scan_token::<nom::error::VerboseError<SourceRef>>(input) {
+            Ok((rest, token)) => {
+                // The parser helpers don't expect comments
+                if let Some(token) = token { if !matches!(token, Token::Comment(_, _)) { tokens.push(token); } }
+                input = rest;
+            },
+            Err(err) => { panic!("Failed to scan '{}': {}", source, err); },
+        }
+    }
+    tokens
+}
+
 /// Runs the given closure on the source text of every file in the `tests` directory.
 /// 
 /// # Arguments
@@ -93,7 +122,7 @@ where
     let mut f: F = f;
 
     // Start by reading the directory
-    let entries: ReadDir = match fs::read_dir(&TEST_DIR) {
+    let entries: ReadDir = match fs::read_dir(TEST_DIR) {
         Ok(entries) => entries,
         Err(err)    => { panic!("Failed to read directory '{}': {}", TEST_DIR, err); },  
     };
@@ -4,7 +4,7 @@
 //  Created:
 //    08 Oct 2022, 20:33:31
 //  Last edited:
-//    27 Oct 2022, 18:02:43
+//    16 Oct 2026, 19:01:32
 //  Auto updated?
 //    Yes
 // 
@@ -16,7 +16,7 @@ use std::fmt::{Debug, Display, Formatter, Result as FResult};
 use std::mem;
 use std::ops::Index;
 
-use crate::spec::Node;
+use crate::spec::{Node, TextRange};
 use crate::source::{SourceRef, SourceText};
 
 
@@ -39,7 +39,7 @@ impl<'a> TokenList<'a> {
     #[inline]
     pub fn new(tokens: &'a [Token<SourceRef<'a>>]) -> Self {
         Self {
-            tokens : tokens.as_ref(),
+            tokens,
         }
     }
 
@@ -57,7 +57,7 @@ impl<'a> TokenList<'a> {
 
     /// Returns an iterator over the TokenList.
     #[inline]
-    pub fn iter<'b>(&'a self) -> std::slice::Iter<'a, Token<SourceRef<'a>>> { self.into_iter() }
+    pub fn iter(&'a self) -> std::slice::Iter<'a, Token<SourceRef<'a>>> { self.into_iter() }
 }
 
 impl<'a> nom::InputTake for TokenList<'a> {
@@ -68,7 +68,7 @@ impl<'a> nom::InputTake for TokenList<'a> {
 
     fn take_split(&self, count: usize) -> (Self, Self) {
         if count > self.len() { panic!("Cannot split {} elements from TokenList of {} elements", count, self.len()); }
-        (TokenList::new(&self.tokens[..count]), TokenList::new(&self.tokens[count..]))
+        (TokenList::new(&self.tokens[count..]), TokenList::new(&self.tokens[..count]))
     }
 }
 impl<'a> nom::InputLength for TokenList<'a> {
@@ -121,7 +121,7 @@ impl<'a> IntoIterator for TokenList<'a> {
         self.tokens.iter()
     }
 }
-impl<'a, 'b> IntoIterator for &'b TokenList<'a> {
+impl<'a> IntoIterator for &TokenList<'a> {
     type Item     = &'a Token<SourceRef<'a>>;
     type IntoIter = std::slice::Iter<'a, Token<SourceRef<'a>>>;
 
@@ -146,6 +146,8 @@ pub enum Token<T> {
     Identifier(String, Option<T>),
     /// A port number (unparsed as of yet)
     Port(String, Option<T>),
+    /// A literal (IPv4) IP address (e.g., `127.0.0.1`)
+    IpAddress(String, Option<T>),
     /// An aterisk, possibly named.
     Aterisk(Option<String>, Option<T>),
 
@@ -158,6 +160,9 @@ pub enum Token<T> {
     /// A boolean value.
     Bool(String, Option<T>),
 
+    /// A comment, including its `//`, `#` or `/* */` markers
+    Comment(String, Option<T>),
+
     /// The `[settings]` keyword/section
     SettingsSection(Option<T>),
     /// The `[rules]` keyword/section
@@ -183,6 +188,44 @@ pub enum Token<T> {
     Comma(Option<T>),
 }
 
+impl<T> Token<T> {
+    /// Returns the reference to the source text that this token is created from. If it does not have such an origin, returns None.
+    /// 
+    /// # Returns
+    /// The source reference if this Token had a source.
+    pub fn source(&self) -> &Option<T> {
+        use Token::*;
+        match self {
+            Action(_, source)     => source,
+            Protocol(_, source)   => source,
+            Identifier(_, source) => source,
+            Port(_, source)       => source,
+            IpAddress(_, source)  => source,
+            Aterisk(_, source)    => source,
+
+            String(_, source) => source,
+            UInt(_, source)   => source,
+            SInt(_, source)   => source,
+            Bool(_, source)   => source,
+
+            Comment(_, source) => source,
+
+            SettingsSection(source) => source,
+            RulesSection(source)    => source,
+
+            Arrow(source)   => source,
+            LSquare(source) => source,
+            RSquare(source) => source,
+            LCurly(source)  => source,
+            RCurly(source)  => source,
+            Colon(source)   => source,
+            Slash(source)   => source,
+            Dot(source)     => source,
+            Comma(source)   => source,
+        }
+    }
+}
+
 impl<T> Display for Token<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
         use Token::*;
@@ -191,6 +234,7 @@ impl<T> Display for Token<T> {
             Protocol(prot, _) => write!(f, "PROTOCOL<{}>", prot),
             Identifier(id, _) => write!(f, "IDENTIFIER<{}>", id),
             Port(port, _)     => write!(f, "PORT<{}>", port),
+            IpAddress(ip, _)  => write!(f, "IPADDRESS<{}>", ip),
             Aterisk(name, _)  => write!(f, "ATERISK{}", if let Some(name) = name { format!("<{}>", name) } else { std::string::String::new() }),
 
             String(val, _)    => write!(f, "STRING<\"{}\">", val),
@@ -198,6 +242,8 @@ impl<T> Display for Token<T> {
             SInt(val, _)      => write!(f, "SINT<{}>", val),
             Bool(val, _)      => write!(f, "BOOL<{}>", val),
 
+            Comment(text, _) => write!(f, "COMMENT<{}>", text),
+
             SettingsSection(_) => write!(f, "SETTINGS_SECTION"),
             RulesSection(_)    => write!(f, "RULES_SECTION"),
 
@@ -214,38 +260,13 @@ impl<T> Display for Token<T> {
     }
 }
 
-impl<T> Node<T> for Token<T>
-where
-    T: Clone + Debug,
-{
-    fn source<'a>(&'a self) -> &'a Option<T> {
-        use Token::*;
-        match self {
-            Action(_, source)     => source,
-            Protocol(_, source)   => source,
-            Identifier(_, source) => source,
-            Port(_, source)       => source,
-            Aterisk(_, source)    => source,
-
-            String(_, source) => source,
-            UInt(_, source)   => source,
-            SInt(_, source)   => source,
-            Bool(_, source)   => source,
-
-            SettingsSection(source) => source,
-            RulesSection(source)    => source,
-
-            Arrow(source)   => source,
-            LSquare(source) => source,
-            RSquare(source) => source,
-            LCurly(source)  => source,
-            RCurly(source)  => source,
-            Colon(source)   => source,
-            Slash(source)   => source,
-            Dot(source)     => source,
-            Comma(source)   => source,
-        }
-    }
+impl<'a> Node for Token<SourceRef<'a>> {
+    #[inline]
+    fn range(&self) -> TextRange { if let Some(source) = self.source() { source.range() } else { TextRange::None } }
+}
+impl Node for Token<SourceText> {
+    #[inline]
+    fn range(&self) -> TextRange { if let Some(source) = self.source() { source.range() } else { TextRange::None } }
 }
 
 impl<T> PartialEq for Token<T> {
@@ -253,11 +274,6 @@ impl<T> PartialEq for Token<T> {
     fn eq(&self, other: &Self) -> bool {
         mem::discriminant(self) == mem::discriminant(other)
     }
-
-    #[inline]
-    fn ne(&self, other: &Self) -> bool {
-        !self.eq(other)
-    }
 }
 
 impl<'a> From<Token<SourceRef<'a>>> for Token<SourceText> {
@@ -268,6 +284,7 @@ impl<'a> From<Token<SourceRef<'a>>> for Token<SourceText> {
             Protocol(prot, source) => Protocol(prot, source.map(|s| s.into())),
             Identifier(id, source) => Identifier(id, source.map(|s| s.into())),
             Port(port, source)     => Port(port, source.map(|s| s.into())),
+            IpAddress(ip, source)  => IpAddress(ip, source.map(|s| s.into())),
             Aterisk(name, source)  => Aterisk(name, source.map(|s| s.into())),
 
             String(val, source) => String(val, source.map(|s| s.into())),
@@ -275,6 +292,8 @@ impl<'a> From<Token<SourceRef<'a>>> for Token<SourceText> {
             SInt(val, source)   => SInt(val, source.map(|s| s.into())),
             Bool(val, source)   => Bool(val, source.map(|s| s.into())),
 
+            Comment(text, source) => Comment(text, source.map(|s| s.into())),
+
             SettingsSection(source) => SettingsSection(source.map(|s| s.into())),
             RulesSection(source)    => RulesSection(source.map(|s| s.into())),
 
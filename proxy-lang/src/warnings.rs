@@ -4,7 +4,7 @@
 //  Created:
 //    07 Oct 2022, 21:50:27
 //  Last edited:
-//    07 Oct 2022, 21:50:57
+//    16 Oct 2026, 18:57:00
 //  Auto updated?
 //    Yes
 // 
@@ -13,5 +13,228 @@
 //!   compiling the proxy language.
 // 
 
+use std::fmt::{Debug, Display, Formatter, Result as FResult};
+
+use console::{style, Style};
+
+use crate::spec::TextRange;
+use crate::source::SourceRef;
+
+
+/***** TESTS *****/
+#[cfg(test)]
+mod tests {
+    use crate::compiler::compile;
+    use crate::loader::load;
+    use super::*;
+
+    #[test]
+    fn test_prettyprint() {
+        console::set_colors_enabled(false);
+        let source: &str = "[settings]\nports: [ 80, 80 ],\n";
+        let (_, warnings): (_, Vec<Warning>) = compile(&load("<test>", source).unwrap()).unwrap();
+        assert_eq!(warnings.len(), 1);
+
+        // Without the source text, only the position is known
+        assert_eq!(format!("{}", warnings[0].prettyprint()), "warning: Port 80 is listed multiple times; only the first one is used\n --> line 2, column 14\n\n");
+
+        // With it, both occurrences are shown like errors are
+        assert_eq!(format!("{}", warnings[0].prettyprint_with("<test>", source)), concat!(
+            "warning: Port 80 is listed multiple times; only the first one is used\n",
+            " --> <test>:2:14\n",
+            "  |\n",
+            "2 | ports: [ 80, 80 ],\n",
+            "  |              ^^\n",
+            "note: previously given here\n",
+            " --> <test>:2:10\n",
+            "  |\n",
+            "2 | ports: [ 80, 80 ],\n",
+            "  |          ^^\n",
+            "\n",
+        ));
+
+        // Warnings without a position fall back to the plain version
+        let warning: Warning = Warning::DuplicatePort{ port: 80, range: TextRange::None, prev: TextRange::None };
+        assert_eq!(format!("{}", warning.prettyprint_with("<test>", source)), format!("{}", warning.prettyprint()));
+    }
+}
+
+
+
+
+
+/***** AUXILLARY *****/
+/// Defines a helper struct that can pretty print the given warning.
+#[derive(Debug)]
+pub struct WarningPrettyPrinter<'a> {
+    /// The warning to pretty print.
+    warn   : &'a dyn PrettyWarning,
+    /// The source text that the warning was produced for, if known.
+    source : Option<SourceRef<'a>>,
+}
+
+impl<'a> Display for WarningPrettyPrinter<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        self.warn.prettyprint_fmt(self.source.as_ref(), f)
+    }
+}
+
+
+
+/// Marks that a warning is a pretty warning (i.e., a non-fatal diagnostic that can be shown to the user).
+pub trait PrettyWarning: Debug + Display {
+    // Child overloadable
+    /// Prints the warning as a warning with no relation to the source text.
+    /// 
+    /// # Arguments
+    /// - `f`: The Formatter to write to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write somehow.
+    fn prettyprint_plain(&self, _f: &mut Formatter<'_>) -> FResult { Ok(()) }
+
+    /// Prints the warning as a simple warning with a marked area in the source text.
+    /// 
+    /// # Arguments
+    /// - `source`: The (whole) source text that the warning was produced for.
+    /// - `f`: The Formatter to write to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write somehow.
+    fn prettyprint_source(&self, _source: &SourceRef, _f: &mut Formatter<'_>) -> FResult { Ok(()) }
+
+
+
+    // Global
+    /// Returns a WarningPrettyPrinter object that prettyprints this warning.
+    /// 
+    /// # Returns
+    /// A WarningPrettyPrinter object that implements Display.
+    #[inline]
+    fn prettyprint<'a>(&'a self) -> WarningPrettyPrinter<'a> where Self: Sized {
+        WarningPrettyPrinter {
+            warn   : self,
+            source : None,
+        }
+    }
+
+    /// Returns a WarningPrettyPrinter object that prettyprints this warning with an excerpt of the source text it was produced for.
+    /// 
+    /// # Arguments
+    /// - `name`: Some name / path that the user can use to identify the source text.
+    /// - `source`: The (whole) source text that the warning was produced for.
+    /// 
+    /// # Returns
+    /// A WarningPrettyPrinter object that implements Display.
+    #[inline]
+    fn prettyprint_with<'a>(&'a self, name: &'a str, source: &'a str) -> WarningPrettyPrinter<'a> where Self: Sized {
+        WarningPrettyPrinter {
+            warn   : self,
+            source : Some(SourceRef::new(name, source)),
+        }
+    }
+
+    /// Prettyprints the PrettyWarning by calling the method that fits the given context.
+    /// 
+    /// # Arguments
+    /// - `source`: The (whole) source text that the warning was produced for, if known. If given, `prettyprint_source()` is used instead of `prettyprint_plain()`.
+    /// - `f`: The Formatter to write to.
+    /// 
+    /// # Errors
+    /// This function errors if we failed to write somehow.
+    fn prettyprint_fmt(&self, source: Option<&SourceRef>, f: &mut Formatter<'_>) -> FResult {
+        match source {
+            Some(source) => self.prettyprint_source(source, f),
+            None         => self.prettyprint_plain(f),
+        }
+    }
+}
+
+
+
+
 
 /***** LIBRARY *****/
+/// Defines warnings that may occur during compiling.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Warning {
+    /// The given hostname is mapped by multiple rules, so the earlier one is ignored.
+    DuplicateHostname{ hostname: String, range: TextRange, prev: TextRange },
+    /// The given port is listed multiple times, so the later one is ignored.
+    DuplicatePort{ port: u16, range: TextRange, prev: TextRange },
+    /// The given hostname is proxied to an address that the proxy itself listens on, so it would connect to itself.
+    ProxyLoop{ hostname: String, target: String, range: TextRange },
+}
+
+impl Warning {
+    /// Returns the range in the source text where this warning occurred.
+    /// 
+    /// # Returns
+    /// The TextRange of the offending node, or `TextRange::None` if it has no origin in the source text.
+    pub fn range(&self) -> TextRange {
+        use self::Warning::*;
+        match self {
+            DuplicateHostname{ range, .. } => *range,
+            DuplicatePort{ range, .. }     => *range,
+            ProxyLoop{ range, .. }         => *range,
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FResult {
+        use self::Warning::*;
+        match self {
+            DuplicateHostname{ hostname, .. } => write!(f, "Hostname '{}' is mapped by multiple rules; only the last one is used", hostname),
+            DuplicatePort{ port, .. }         => write!(f, "Port {} is listed multiple times; only the first one is used", port),
+            ProxyLoop{ hostname, target, .. } => write!(f, "Hostname '{}' is proxied to '{}', which is the proxy itself", hostname, target),
+        }
+    }
+}
+
+impl PrettyWarning for Warning {
+    fn prettyprint_plain(&self, f: &mut Formatter<'_>) -> FResult {
+        // Print the header with the message
+        writeln!(f, "{}{}", style("warning").bold().yellow(), style(format!(": {}", self)).bold())?;
+
+        // Write the position, if any
+        if let TextRange::Some(start, _) = self.range() {
+            writeln!(f, " {} line {}, column {}", style("-->").bold().blue(), start.line, start.col)?;
+        }
+        writeln!(f)?;
+
+        // Done
+        Ok(())
+    }
+
+    fn prettyprint_source(&self, source: &SourceRef, f: &mut Formatter<'_>) -> FResult {
+        use self::Warning::*;
+
+        // Find the offending area in the source text, falling back to the position only if it's not there
+        let range: SourceRef = match SourceRef::from_range(source.name(), source.source(), self.range()) {
+            Some(range) => range,
+            None        => { return self.prettyprint_plain(f); },
+        };
+
+        // Print the header with the message
+        writeln!(f, "{}{}", style("warning").bold().yellow(), style(format!(": {}", self)).bold())?;
+
+        // Write the source reference
+        write!(f, "{}", range.display(Style::new().bold().yellow()))?;
+
+        // Write where the same thing was given before, if any
+        let prev: TextRange = match self {
+            DuplicateHostname{ prev, .. } => *prev,
+            DuplicatePort{ prev, .. }     => *prev,
+            ProxyLoop{ .. }               => TextRange::None,
+        };
+        if let Some(prev) = SourceRef::from_range(source.name(), source.source(), prev) {
+            writeln!(f, "{}{}", style("note").bold().blue(), style(": previously given here").bold())?;
+            write!(f, "{}", prev.display(Style::new().bold().blue()))?;
+        }
+        writeln!(f)?;
+
+        // Done
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+//  PIPELINE.rs
+//    by Lut99
+// 
+//  Created:
+//    16 Oct 2026, 18:28:59
+//  Last edited:
+//    16 Oct 2026, 19:08:45
+//  Auto updated?
+//    Yes
+// 
+//  Description:
+//!   Tests the public `scan -> parse -> compile` pipeline end to end,
+//!   both stage-by-stage and through the one-shot entrypoints.
+// 
+
+use std::fs;
+
+use proxy_lang::ast;
+use proxy_lang::compiler::{self, Config};
+use proxy_lang::errors::{init_colors, PrettyError as _};
+use proxy_lang::parser;
+use proxy_lang::scanner;
+use proxy_lang::warnings::Warning;
+
+
+/***** TESTS *****/
+#[test]
+fn test_scan_parse_settings() {
+    // Scan a settings block without converting the tokens, then feed them straight to the parser
+    let source: &str = "[settings]\nports: [ 80, 443 ],\naddress: \"127.0.0.1\",\n";
+    let tokens = scanner::scan_borrowed("<test>", source).unwrap();
+    let config: ast::Config = parser::parse(&tokens).unwrap();
+    assert_eq!(config.config.len(), 1);
+    assert_eq!(config.config[0].settings.len(), 2);
+    assert_eq!(config.config[0].settings[0].key.value, "ports");
+    assert_eq!(config.config[0].settings[1].key.value, "address");
+
+    // The result compiles, too
+    let (config, warnings): (Config, Vec<Warning>) = compiler::compile(&config).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(config.ports, vec![ 80, 443 ]);
+    assert_eq!(config.address.to_string(), "127.0.0.1");
+}
+
+#[test]
+fn test_load_sample_file() {
+    // Compile a whole sample file through a single call
+    let path: String = format!("{}/../tests/compile.pconf", env!("CARGO_MANIFEST_DIR"));
+    let source: String = fs::read_to_string(&path).unwrap();
+    let (config, warnings): (Config, Vec<Warning>) = proxy_lang::compile_to_proxy_config(&path, &source).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(config.ports, vec![ 80, 443 ]);
+    assert_eq!(config.hostnames.get("test.nl").map(|t| t.as_str()), Some("example.com"));
+
+    // Loading it gives the same AST as the stage-by-stage pipeline
+    let tokens = scanner::scan_borrowed(&path, &source).unwrap();
+    assert_eq!(proxy_lang::load(&path, &source).unwrap(), parser::parse(&tokens).unwrap());
+
+    // Errors of all stages can be pretty-printed as one
+    init_colors(true);
+    let err = proxy_lang::load("<test>", "[rules]\na.nl ->,\nb.nl ->,\n").unwrap_err();
+    let text: String = format!("{}", err.prettyprint());
+    assert_eq!(text.matches("error[").count(), 2);
+    assert_eq!(text.matches(" --> <test>:2:8\n").count(), 1);
+    assert_eq!(text.matches(" --> <test>:3:8\n").count(), 1);
+}